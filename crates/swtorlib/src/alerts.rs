@@ -0,0 +1,323 @@
+use chrono::{Duration, NaiveDateTime, NaiveTime};
+use std::time::Instant;
+
+#[cfg(feature = "serde")]
+use serde::Deserialize;
+#[cfg(feature = "serde")]
+use std::path::Path;
+
+#[cfg(feature = "serde")]
+use crate::parser::config_dir;
+pub use crate::parser::trigger::Trigger;
+use crate::parser::{actor::Actor, line::Line, namedid::NamedID};
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+pub struct Rule {
+	pub name: String,
+	pub trigger: Trigger,
+	// countdown in seconds
+	pub duration: i64,
+	pub text: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+pub struct Rules {
+	#[cfg_attr(feature = "serde", serde(default, rename = "rule"))]
+	pub rules: Vec<Rule>,
+}
+
+#[cfg(feature = "serde")]
+impl Rules {
+	// [[rule]]
+	// name = "Brontes Kinetic Bombardment"
+	// duration = 10
+	// trigger = { type = "ability_activated", ability = 3294098182111232, npc = 3266825467691008 }
+	pub fn from_toml(data: &str) -> std::io::Result<Self> {
+		toml::from_str(data).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+	}
+
+	// { "rule": [{ "name": "...", "duration": 10, "trigger": { "type": "health_below", ... } }] }
+	pub fn from_json(data: &str) -> std::io::Result<Self> {
+		serde_json::from_str(data)
+			.map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+	}
+
+	// <config dir>/swtormeter/alerts.toml (or alerts.json) if it exists
+	pub fn default_path() -> Option<String> {
+		Self::find(&config_dir()?)
+	}
+
+	// alerts.toml or alerts.json in dir
+	pub fn find(dir: &Path) -> Option<String> {
+		["alerts.toml", "alerts.json"]
+			.iter()
+			.map(|f| dir.join(f))
+			.find(|p| p.is_file())
+			.map(|p| p.display().to_string())
+	}
+
+	pub fn load(path: &str) -> std::io::Result<Self> {
+		let data = std::fs::read_to_string(path)?;
+		if path.ends_with(".json") {
+			Self::from_json(&data)
+		} else {
+			Self::from_toml(&data)
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+	pub name: String,
+	pub text: String,
+	// full timestamps so a countdown going past midnight doesn't wrap
+	pub fired: NaiveDateTime,
+	pub expires: NaiveDateTime,
+}
+
+impl Alert {
+	pub fn remaining(&self, now: NaiveDateTime) -> Duration {
+		self.expires - now
+	}
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AlertEngine {
+	pub rules: Vec<Rule>,
+	pub local_player: Option<NamedID>,
+
+	active: Vec<Alert>,
+	fired_once: Vec<usize>,
+	// npcs seen since the last reset
+	npcs: Vec<u64>,
+	ts: NaiveDateTime,
+	// when the last line was processed, the countdowns keep running between lines
+	at: Option<Instant>,
+}
+
+impl AlertEngine {
+	pub fn new(rules: Rules) -> Self {
+		Self {
+			rules: rules.rules,
+			..Self::default()
+		}
+	}
+
+	// call on every new encounter
+	pub fn reset(&mut self) {
+		self.active.clear();
+		self.fired_once.clear();
+		self.npcs.clear();
+	}
+
+	pub fn process(&mut self, l: &Line) -> Vec<Alert> {
+		self.ts = self.stamp(l.ts);
+		self.at = Some(Instant::now());
+		let ts = self.ts;
+		self.active.retain(|a| a.expires > ts);
+
+		let mut spawned = Vec::new();
+		for a in [&l.source, &l.target].into_iter().flatten() {
+			if a.is_npc() && !self.npcs.contains(&a.id.id) {
+				self.npcs.push(a.id.id);
+				spawned.push(a.id.id);
+			}
+		}
+
+		let mut fired = Vec::new();
+		for (i, r) in self.rules.iter().enumerate() {
+			if self.fired_once.contains(&i) || !self.matches(&r.trigger, l, &spawned) {
+				continue;
+			}
+			if r.trigger.once() {
+				self.fired_once.push(i);
+			}
+
+			let a = Alert {
+				name: r.name.clone(),
+				text: r.text.clone().unwrap_or_else(|| r.name.clone()),
+				fired: ts,
+				expires: ts + Duration::seconds(r.duration),
+			};
+			// a rule firing again restarts its timer
+			self.active.retain(|o| o.name != a.name);
+			self.active.push(a.clone());
+			fired.push(a);
+		}
+		self.active.sort_by_key(|a| a.expires);
+
+		fired
+	}
+
+	// the line's time of day as a full timestamp, the log only goes back in time when it
+	// went past midnight
+	fn stamp(&self, ts: NaiveTime) -> NaiveDateTime {
+		let t = self.ts.date().and_time(ts);
+		if self.ts - t > Duration::hours(12) {
+			t + Duration::days(1)
+		} else {
+			t
+		}
+	}
+
+	// the log time of the last line plus the wall-clock time between it and wall
+	pub fn now_at(&self, wall: Instant) -> NaiveDateTime {
+		let since = self.at.map_or(Duration::zero(), |at| {
+			Duration::from_std(wall.saturating_duration_since(at)).unwrap_or(Duration::zero())
+		});
+		self.ts + since
+	}
+
+	pub fn now(&self) -> NaiveDateTime {
+		self.now_at(Instant::now())
+	}
+
+	// active alerts, soonest first
+	pub fn active(&self) -> impl Iterator<Item = (&Alert, Duration)> {
+		self.active_at(Instant::now())
+	}
+
+	// active alerts as of the wall-clock time wall
+	pub fn active_at(&self, wall: Instant) -> impl Iterator<Item = (&Alert, Duration)> {
+		let now = self.now_at(wall);
+		self.active
+			.iter()
+			.filter(move |a| a.expires > now)
+			.map(move |a| (a, a.remaining(now)))
+	}
+
+	fn is_local(&self, a: &Actor) -> bool {
+		match &self.local_player {
+			Some(id) => &a.id == id,
+			None => a.is_player(),
+		}
+	}
+
+	fn matches(&self, t: &Trigger, l: &Line, spawned: &[u64]) -> bool {
+		if !t.matches(l, spawned) {
+			return false;
+		}
+		match t {
+			Trigger::EffectApplied { .. } => l
+				.target
+				.as_ref()
+				.or(l.source.as_ref())
+				.is_some_and(|a| self.is_local(a)),
+			_ => true,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+
+	const BRONTES: u64 = 3266825467691008;
+
+	fn rules() -> Rules {
+		Rules {
+			rules: vec![
+				Rule {
+					name: "Kinetic Bombardment".to_owned(),
+					trigger: Trigger::AbilityActivated {
+						ability: 2,
+						npc: Some(BRONTES),
+					},
+					duration: 10,
+					text: None,
+				},
+				Rule {
+					name: "Burn".to_owned(),
+					trigger: Trigger::HealthBelow {
+						npc: BRONTES,
+						pct: 30.,
+					},
+					duration: 5,
+					text: Some("burn phase, use cooldowns".to_owned()),
+				},
+			],
+		}
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn rules_from_toml() {
+		let parsed = Rules::from_toml(
+			r#"
+			[[rule]]
+			name = "Kinetic Bombardment"
+			duration = 10
+			trigger = { type = "ability_activated", ability = 2, npc = 3266825467691008 }
+
+			[[rule]]
+			name = "Burn"
+			duration = 5
+			text = "burn phase, use cooldowns"
+			trigger = { type = "health_below", npc = 3266825467691008, pct = 30.0 }
+			"#,
+		)
+		.unwrap();
+		assert_eq!(parsed, rules());
+	}
+
+	#[test]
+	fn timers() {
+		let mut eng = AlertEngine::new(rules());
+
+		let cast = Line::new("[20:00:01.000] [Dread Master Brontes {3266825467691008}:1|(0,0,0,0)|(500/1000)] [] [Kinetic Bombardment {2}] [Event {836045448945472}: AbilityActivate {836045448945479}]").unwrap();
+		let fired = eng.process(&cast);
+		assert_eq!(fired.len(), 1);
+		assert_eq!(fired[0].name, "Kinetic Bombardment");
+
+		let hit = Line::new("[20:00:04.000] [@Tank#1|(0,0,0,0)|(100/100)] [Dread Master Brontes {3266825467691008}:1|(0,0,0,0)|(200/1000)] [Strike {1}] [ApplyEffect {836045448945477}: Damage {836045448945501}] (300 kinetic {836045448940873})").unwrap();
+		assert_eq!(eng.process(&hit)[0].text, "burn phase, use cooldowns");
+		assert!(eng.process(&hit).is_empty());
+
+		// as of the last line
+		let active = eng
+			.active_at(eng.at.unwrap())
+			.map(|(a, d)| (a.name.as_str(), d.num_seconds()))
+			.collect::<Vec<_>>();
+		assert_eq!(active, vec![("Burn", 5), ("Kinetic Bombardment", 7)]);
+	}
+
+	#[test]
+	fn wall_clock() {
+		let mut eng = AlertEngine::new(rules());
+		let cast = Line::new("[20:00:01.000] [Dread Master Brontes {3266825467691008}:1|(0,0,0,0)|(500/1000)] [] [Kinetic Bombardment {2}] [Event {836045448945472}: AbilityActivate {836045448945479}]").unwrap();
+		eng.process(&cast);
+		let at = eng.at.unwrap();
+
+		// no lines for 4 seconds
+		let active = eng
+			.active_at(at + std::time::Duration::from_secs(4))
+			.map(|(_, d)| d.num_seconds())
+			.collect::<Vec<_>>();
+		assert_eq!(active, vec![6]);
+
+		assert_eq!(
+			eng.active_at(at + std::time::Duration::from_secs(11))
+				.count(),
+			0
+		);
+	}
+
+	#[test]
+	fn past_midnight() {
+		let mut eng = AlertEngine::new(rules());
+		let cast = Line::new("[23:59:55.000] [Dread Master Brontes {3266825467691008}:1|(0,0,0,0)|(500/1000)] [] [Kinetic Bombardment {2}] [Event {836045448945472}: AbilityActivate {836045448945479}]").unwrap();
+		eng.process(&cast);
+
+		let hit = Line::new("[00:00:01.000] [@Tank#1|(0,0,0,0)|(100/100)] [Dread Master Brontes {3266825467691008}:1|(0,0,0,0)|(500/1000)] [Strike {1}] [ApplyEffect {836045448945477}: Damage {836045448945501}] (10 kinetic {836045448940873})").unwrap();
+		eng.process(&hit);
+		let at = eng.at.unwrap();
+		let active = eng
+			.active_at(at)
+			.map(|(a, d)| (a.name.as_str(), d.num_seconds()))
+			.collect::<Vec<_>>();
+		assert_eq!(active, vec![("Kinetic Bombardment", 4)]);
+	}
+}
@@ -0,0 +1,112 @@
+use std::{collections::HashMap, io};
+
+use crate::parser::reader::{decode, encode};
+
+// replaces player names and ids with stable pseudonyms, everything else in the line, including
+// npc, companion, ability and effect names, is kept byte for byte
+#[derive(Debug, Clone, Default)]
+pub struct Anonymizer {
+	// (name, id) -> pseudonym number, in order of first appearance
+	players: HashMap<(String, String), usize>,
+}
+
+impl Anonymizer {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	// only the source and target fields can name a player
+	pub fn line(&mut self, l: &str) -> String {
+		let mut out = String::with_capacity(l.len());
+		let mut rest = l;
+		for i in 0..3 {
+			let Some(end) = rest.find(']') else {
+				break;
+			};
+			let field = &rest[..end];
+			if i == 0 {
+				out.push_str(field);
+			} else {
+				out.push_str(&self.actor(field));
+			}
+			out.push(']');
+			rest = &rest[end + 1..];
+		}
+		out.push_str(rest);
+		out
+	}
+
+	// @Name#id|..., @Name#id/Companion {id}:instance|... or @Name|... in older logs
+	fn actor(&mut self, field: &str) -> String {
+		let Some(at) = field.find('@') else {
+			return field.to_owned();
+		};
+		let player = &field[at + 1..];
+		let name_end = player.find(['#', '/', '|']).unwrap_or(player.len());
+		let name = &player[..name_end];
+		let mut rest = &player[name_end..];
+
+		let mut id = "";
+		if let Some(r) = rest.strip_prefix('#') {
+			let end = r.find(['/', '|']).unwrap_or(r.len());
+			id = &r[..end];
+			rest = &r[end..];
+		}
+
+		let next = self.players.len() + 1;
+		let n = *self
+			.players
+			.entry((name.to_owned(), id.to_owned()))
+			.or_insert(next);
+
+		let mut out = format!("{}@Player{n}", &field[..at]);
+		if !id.is_empty() {
+			out += &format!("#{n}");
+		}
+		out + rest
+	}
+
+	// line endings are kept as they are
+	pub fn text(&mut self, text: &str) -> String {
+		text.split_inclusive('\n')
+			.map(|l| {
+				let body = l.trim_end_matches(['\r', '\n']);
+				self.line(body) + &l[body.len()..]
+			})
+			.collect()
+	}
+}
+
+pub fn anonymize_file(input: &str, output: &str) -> io::Result<()> {
+	let text = decode(&std::fs::read(input)?);
+	std::fs::write(output, encode(&Anonymizer::new().text(&text)))
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	use crate::parser::line::Line;
+
+	#[test]
+	fn pseudonyms() {
+		let log = "\
+[20:00:00.000] [@Tank Name#689203062937467|(0,0,0,0)|(100/100)] [] [] [AreaEntered {836045448953664}: The Dread Fortress {833571547775668} 8 Player Veteran {836045448953652}]\r
+[20:00:02.000] [@Tank Name#689203062937467/Khem Val {3}:7|(0,0,0,0)|(50/50)] [Dread Master Brontes {3266825467691008}:1|(0,0,0,0)|(770/1000)] [Strike {1}] [ApplyEffect {836045448945477}: Damage {836045448945501}] (230 kinetic {836045448940873})\r
+[20:00:03.000] [@Heals#689203062937468|(0,0,0,0)|(100/100)] [@Tank Name#689203062937467|(0,0,0,0)|(100/100)] [Heal {3}] [ApplyEffect {836045448945477}: Heal {836045448945500}] (300)\r
+[20:00:04.000] [@Heals#689203062937468|(0,0,0,0)|(100/100)] [=] [Heal {3}] [ApplyEffect {836045448945477}: Heal {836045448945500}] (300)\r
+";
+		let out = Anonymizer::new().text(log);
+		assert_eq!(out.lines().count(), 4);
+		assert!(!out.contains("Tank") && !out.contains("Heals") && !out.contains("68920306"));
+		assert!(out.contains("[@Player1#1/Khem Val {3}:7|(0,0,0,0)|(50/50)] [Dread Master Brontes"));
+		assert!(out.contains("[@Player2#2|(0,0,0,0)|(100/100)] [@Player1#1|(0,0,0,0)|(100/100)]"));
+		assert!(out.ends_with("(300)\r\n"));
+
+		for (a, b) in log.lines().zip(out.lines()) {
+			let (a, b) = (Line::new(a.trim()).unwrap(), Line::new(b.trim()).unwrap());
+			assert_eq!(a.ts, b.ts);
+			assert_eq!(format!("{:?}", a.action), format!("{:?}", b.action));
+		}
+	}
+}
@@ -0,0 +1,180 @@
+use std::io::{self, Write};
+
+use crate::parser::{
+	actor_stats::{ActorStats, MeterRow},
+	encounter::{Encounter, PlayerRow},
+};
+
+const METER_HEADER: &str = "casts,total,crits,crit_pct,apm,xps";
+
+// one row per player and table for every encounter
+pub fn write_players<W: Write>(w: &mut W, encs: &[Encounter]) -> io::Result<()> {
+	writeln!(
+		w,
+		"encounter,start,player,discipline,role,table,{METER_HEADER}"
+	)?;
+	for e in encs {
+		let tables: [(&str, Vec<PlayerRow>); 4] = [
+			("healing", e.heals_out_rows()),
+			("damage", e.dmg_out_rows()),
+			("healing_taken", e.heals_in_rows()),
+			("damage_taken", e.dmg_in_rows()),
+		];
+		for (table, rows) in tables {
+			for r in rows {
+				let prefix = [
+					e.name(),
+					e.start.to_string(),
+					r.id.name,
+					r.spec,
+					r.role.name().to_owned(),
+					table.to_owned(),
+				];
+				write_row(w, &prefix, &r.meter)?;
+			}
+		}
+	}
+	Ok(())
+}
+
+// one row per ability and per target for every player of every encounter
+pub fn write_breakdown<W: Write>(w: &mut W, encs: &[Encounter]) -> io::Result<()> {
+	writeln!(w, "encounter,start,player,kind,name,{METER_HEADER}")?;
+	for e in encs {
+		// an encounter can end in the second it started
+		let elapsed = e.elapsed().num_seconds().max(1);
+		for p in e.players.iter() {
+			let kinds = [
+				("ability", &p.spells_out),
+				("damage_target", &p.dmg_out),
+				("healing_target", &p.heal_out),
+				("damage_source", &p.dmg_in),
+				("healing_source", &p.heal_in),
+			];
+			for (kind, v) in kinds {
+				for m in ActorStats::meter_rows(v, elapsed) {
+					let prefix = [
+						e.name(),
+						e.start.to_string(),
+						p.id.name.clone(),
+						kind.to_owned(),
+						m.name.clone(),
+					];
+					write_row(w, &prefix, &m)?;
+				}
+			}
+		}
+	}
+	Ok(())
+}
+
+fn write_row<W: Write>(w: &mut W, prefix: &[String], m: &MeterRow) -> io::Result<()> {
+	for f in prefix {
+		write!(w, "{},", quote(f))?;
+	}
+	writeln!(
+		w,
+		"{},{},{},{:.2},{:.2},{:.2}",
+		m.casts, m.total, m.crits, m.crit_pct, m.apm, m.xps
+	)
+}
+
+fn quote(s: &str) -> String {
+	if s.contains([',', '"', '\n', '\r']) {
+		format!("\"{}\"", s.replace('"', "\"\""))
+	} else {
+		s.to_owned()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	use crate::{
+		fixtures::BRONTES,
+		parser::{encounter::Encounters, line::Line},
+	};
+
+	fn encounters(log: &str) -> Vec<Encounter> {
+		let mut encs = Encounters::new();
+		for l in log.lines() {
+			encs.push(Line::new(l).unwrap(), &|_, _| {});
+		}
+		encs.finish()
+	}
+
+	fn write<F: Fn(&mut Vec<u8>, &[Encounter]) -> io::Result<()>>(f: F, log: &str) -> String {
+		let mut out = Vec::new();
+		f(&mut out, &encounters(log)).unwrap();
+		String::from_utf8(out).unwrap()
+	}
+
+	#[test]
+	fn players() {
+		let out = write(write_players, BRONTES);
+		let lines = out.lines().collect::<Vec<_>>();
+		assert_eq!(
+			lines[0],
+			"encounter,start,player,discipline,role,table,casts,total,crits,crit_pct,apm,xps"
+		);
+		assert!(lines.contains(
+			&"Brontes (8m Veteran),20:00:01,Tank,,Unknown,damage,1,230,0,0.00,20.00,76.67"
+		));
+		assert!(lines.contains(
+			&"Brontes (8m Veteran),20:00:01,Tank,,Unknown,damage_taken,1,100,0,0.00,20.00,33.33"
+		));
+		assert!(lines.contains(
+			&"Brontes (8m Veteran),20:01:00,Tank,,Unknown,damage,1,1000,1,100.00,30.00,500.00"
+		));
+		// 4 tables for one player in 2 encounters
+		assert_eq!(lines.len(), 1 + 8);
+	}
+
+	#[test]
+	fn breakdown() {
+		let out = write(write_breakdown, BRONTES);
+		let lines = out.lines().collect::<Vec<_>>();
+		assert_eq!(
+			lines[0],
+			"encounter,start,player,kind,name,casts,total,crits,crit_pct,apm,xps"
+		);
+		assert!(lines.contains(
+			&"Brontes (8m Veteran),20:01:00,Tank,ability,Strike,1,1000,1,100.00,30.00,500.00"
+		));
+		assert!(lines.contains(
+			&"Brontes (8m Veteran),20:00:01,Tank,damage_target,Dread Master Brontes,1,230,0,0.00,20.00,76.67"
+		));
+		assert_eq!(
+			lines
+				.iter()
+				.filter(|l| l.contains(",damage_source,"))
+				.count(),
+			1
+		);
+	}
+
+	#[test]
+	fn zero_length_encounter() {
+		// enter and exit combat in the same second
+		let log = "\
+[20:00:01.000] [@Tank#1|(0,0,0,0)|(100/100)] [] [] [Event {836045448945472}: EnterCombat {836045448945489}]
+[20:00:01.000] [@Tank#1|(0,0,0,0)|(100/100)] [Training Droid {5}:1|(0,0,0,0)|(800/1000)] [Strike {1}] [ApplyEffect {836045448945477}: Damage {836045448945501}] (200 kinetic {836045448940873})
+[20:00:01.000] [@Tank#1|(0,0,0,0)|(100/100)] [] [] [Event {836045448945472}: ExitCombat {836045448945490}]
+";
+		for out in [write(write_players, log), write(write_breakdown, log)] {
+			assert!(!out.contains("inf") && !out.contains("NaN"), "{out}");
+			assert!(out.contains(",1,200,0,0.00,60.00,200.00"), "{out}");
+		}
+	}
+
+	#[test]
+	fn quoting() {
+		assert_eq!(quote("Brontes"), "Brontes");
+		assert_eq!(
+			quote("Brontes (8m Veteran), pull 1"),
+			"\"Brontes (8m Veteran), pull 1\""
+		);
+		assert_eq!(quote("\"Tank\""), "\"\"\"Tank\"\"\"");
+	}
+}
@@ -0,0 +1,2 @@
+// two pulls on Brontes in 8m veteran: a wipe where the tank dies, then a kill
+pub static BRONTES: &str = include_str!("../tests/data/brontes.txt");
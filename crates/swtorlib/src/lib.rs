@@ -1,2 +1,15 @@
+pub mod alerts;
+pub mod anonymize;
+pub mod csv;
+#[cfg(test)]
+mod fixtures;
+pub mod metrics;
 pub mod parser;
-pub use parser::parse;
+pub mod report;
+#[cfg(feature = "share")]
+pub mod share;
+#[cfg(feature = "sqlite")]
+pub mod storage;
+#[cfg(feature = "webhook")]
+pub mod webhook;
+pub use parser::{parse, parse_with};
@@ -0,0 +1,199 @@
+use std::{
+	fmt::Write,
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Mutex, OnceLock,
+	},
+	time::{Duration, Instant},
+};
+
+use chrono::NaiveTime;
+
+use crate::parser::encounter::Encounter;
+
+// per player rates are recomputed at most this often
+const PLAYER_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerRate {
+	pub name: String,
+	pub dps: f64,
+	pub hps: f64,
+}
+
+// counters for the prometheus exporter, fed by the reader and Encounters::process
+#[derive(Debug, Default)]
+pub struct Metrics {
+	lines: AtomicU64,
+	parse_errors: AtomicU64,
+	encounters: AtomicU64,
+	last_line: Mutex<Option<Instant>>,
+	players: Mutex<Players>,
+}
+
+#[derive(Debug, Default)]
+struct Players {
+	start: NaiveTime,
+	updated: Option<Instant>,
+	rates: Vec<PlayerRate>,
+}
+
+impl Metrics {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn global() -> &'static Self {
+		static INSTANCE: OnceLock<Metrics> = OnceLock::new();
+		INSTANCE.get_or_init(Self::new)
+	}
+
+	pub fn line_parsed(&self) {
+		self.lines.fetch_add(1, Ordering::Relaxed);
+		*self.last_line.lock().unwrap() = Some(Instant::now());
+	}
+
+	pub fn parse_error(&self) {
+		self.parse_errors.fetch_add(1, Ordering::Relaxed);
+	}
+
+	pub fn encounter_started(&self) {
+		self.encounters.fetch_add(1, Ordering::Relaxed);
+	}
+
+	// force skips the rate limit, used when the encounter ends
+	pub fn update_players(&self, e: &Encounter, force: bool) {
+		let mut p = self.players.lock().unwrap();
+		let now = Instant::now();
+		let fresh = p.updated.is_some_and(|t| now - t < PLAYER_INTERVAL);
+		if p.start == e.start && fresh && !force {
+			return;
+		}
+		let elapsed = e.elapsed().num_seconds().max(1);
+		p.start = e.start;
+		p.updated = Some(now);
+		p.rates = e
+			.players
+			.iter()
+			.map(|a| PlayerRate {
+				name: a.id.name.clone(),
+				dps: a.all_dmg_out().xps(elapsed),
+				hps: a.all_heal_out().xps(elapsed),
+			})
+			.collect();
+	}
+
+	pub fn lines(&self) -> u64 {
+		self.lines.load(Ordering::Relaxed)
+	}
+
+	pub fn parse_errors(&self) -> u64 {
+		self.parse_errors.load(Ordering::Relaxed)
+	}
+
+	pub fn encounters(&self) -> u64 {
+		self.encounters.load(Ordering::Relaxed)
+	}
+
+	// None until the first line was read
+	pub fn reader_lag(&self) -> Option<Duration> {
+		self.last_line.lock().unwrap().map(|t| t.elapsed())
+	}
+
+	pub fn players(&self) -> Vec<PlayerRate> {
+		self.players.lock().unwrap().rates.clone()
+	}
+
+	// the prometheus text exposition format
+	pub fn render(&self) -> String {
+		let mut out = String::new();
+		let counters = [
+			("lines_parsed_total", "Log lines parsed.", self.lines()),
+			(
+				"parse_errors_total",
+				"Log lines that could not be parsed.",
+				self.parse_errors(),
+			),
+			("encounters_total", "Encounters started.", self.encounters()),
+		];
+		for (name, help, v) in counters {
+			header(&mut out, name, help, "counter");
+			let _ = writeln!(out, "swtormeter_{name} {v}");
+		}
+
+		if let Some(lag) = self.reader_lag() {
+			header(
+				&mut out,
+				"reader_lag_seconds",
+				"Time since the last line was read.",
+				"gauge",
+			);
+			let _ = writeln!(
+				out,
+				"swtormeter_reader_lag_seconds {:.3}",
+				lag.as_secs_f64()
+			);
+		}
+
+		let players = self.players();
+		let help = "Damage per second in the current encounter.";
+		per_player(&mut out, "player_dps", help, &players, |p| p.dps);
+		let help = "Healing per second in the current encounter.";
+		per_player(&mut out, "player_hps", help, &players, |p| p.hps);
+		out
+	}
+}
+
+fn header(out: &mut String, name: &str, help: &str, typ: &str) {
+	let _ = writeln!(out, "# HELP swtormeter_{name} {help}");
+	let _ = writeln!(out, "# TYPE swtormeter_{name} {typ}");
+}
+
+fn per_player(
+	out: &mut String,
+	name: &str,
+	help: &str,
+	players: &[PlayerRate],
+	f: fn(&PlayerRate) -> f64,
+) {
+	header(out, name, help, "gauge");
+	for p in players {
+		let player = label(&p.name);
+		let _ = writeln!(out, "swtormeter_{name}{{player=\"{player}\"}} {:.1}", f(p));
+	}
+}
+
+fn label(v: &str) -> String {
+	v.replace('\\', "\\\\")
+		.replace('"', "\\\"")
+		.replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	#[test]
+	fn render() {
+		let m = Metrics::new();
+		assert!(!m.render().contains("reader_lag"));
+
+		m.line_parsed();
+		m.line_parsed();
+		m.parse_error();
+		m.encounter_started();
+		m.players.lock().unwrap().rates = vec![PlayerRate {
+			name: "Ta\"nk".to_owned(),
+			dps: 1234.56,
+			hps: 0.,
+		}];
+
+		let out = m.render();
+		assert!(out.contains("swtormeter_lines_parsed_total 2\n"));
+		assert!(out.contains("swtormeter_parse_errors_total 1\n"));
+		assert!(out.contains("swtormeter_encounters_total 1\n"));
+		assert!(out.contains("# TYPE swtormeter_reader_lag_seconds gauge\n"));
+		assert!(out.contains("swtormeter_player_dps{player=\"Ta\\\"nk\"} 1234.6\n"));
+		assert!(out.contains("swtormeter_player_hps{player=\"Ta\\\"nk\"} 0.0\n"));
+	}
+}
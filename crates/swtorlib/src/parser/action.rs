@@ -1,8 +1,9 @@
-use crate::parser::{consts::*, namedid::NamedID};
+use crate::parser::{consts::*, namedid::NamedID, utils::extract_until};
 
 use super::actor::Actor;
 
 #[derive(Debug, Clone, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DamageKind {
 	Energy,
 	Kinetic,
@@ -37,9 +38,117 @@ impl DamageKind {
 	}
 }
 
+#[derive(Debug, Clone, Copy, Hash, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ResourceKind {
+	Energy,
+	Force,
+	Heat,
+	Ammo,
+	Rage,
+	Focus,
+
+	#[default]
+	Unknown,
+}
+
+impl ResourceKind {
+	// the effect name is localized, the ids of the other resources aren't known yet so they
+	// still go by their english name
+	pub fn new(effect: &NamedID) -> Self {
+		match effect.id {
+			RESOURCE_ENERGY => return Self::Energy,
+			RESOURCE_HEAT => return Self::Heat,
+			_ => {}
+		}
+		match effect.name.to_lowercase().as_str() {
+			"energy" => Self::Energy,
+			"force" => Self::Force,
+			"heat" => Self::Heat,
+			"ammo" => Self::Ammo,
+			"rage" => Self::Rage,
+			"focus" => Self::Focus,
+			_ => Self::Unknown,
+		}
+	}
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			Self::Energy => "Energy",
+			Self::Force => "Force",
+			Self::Heat => "Heat",
+			Self::Ammo => "Ammo",
+			Self::Rage => "Rage",
+			Self::Focus => "Focus",
+			Self::Unknown => "Unknown",
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, Hash, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Mode {
+	Story,
+	Veteran,
+	Master,
+
+	#[default]
+	Unknown,
+}
+
+#[derive(Debug, Clone, Copy, Hash, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Difficulty {
+	pub size: u8,
+	pub mode: Mode,
+}
+
+impl Difficulty {
+	// "8 Player Veteran {836045448953652}"
+	pub fn new(p: &str) -> Self {
+		let name = extract_until(p, '{').trim().to_lowercase();
+		let size = name.split(' ').find_map(|w| w.parse().ok()).unwrap_or(0);
+		let mode = if name.contains("story") || name.contains("histoire") {
+			Mode::Story
+		} else if name.contains("veteran") || name.contains("vétéran") {
+			Mode::Veteran
+		} else if name.contains("master") || name.contains("meister") || name.contains("maître") {
+			Mode::Master
+		} else {
+			Mode::Unknown
+		};
+
+		Self { size, mode }
+	}
+
+	pub fn is_known(&self) -> bool {
+		self.size > 0 || self.mode != Mode::Unknown
+	}
+}
+
+impl std::fmt::Display for Difficulty {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let mode = match self.mode {
+			Mode::Story => "Story",
+			Mode::Veteran => "Veteran",
+			Mode::Master => "Master",
+			Mode::Unknown => "",
+		};
+		match (self.size, mode.is_empty()) {
+			(0, _) => write!(f, "{}", mode),
+			(size, true) => write!(f, "{}m", size),
+			(size, false) => write!(f, "{}m {}", size, mode),
+		}
+	}
+}
+
 #[derive(Debug, Clone, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Action {
-	AreaEntered(NamedID),
+	AreaEntered {
+		area: NamedID,
+		difficulty: Difficulty,
+	},
 	EnterCombat,
 	ExitCombat,
 
@@ -57,8 +166,14 @@ pub enum Action {
 	ModifyThreat(NamedID, i32),
 	ModifyCharges(NamedID),
 
-	Spend,
-	Restore,
+	Spend {
+		kind: ResourceKind,
+		value: i32,
+	},
+	Restore {
+		kind: ResourceKind,
+		value: i32,
+	},
 
 	Death,
 	Revived,
@@ -104,12 +219,13 @@ pub enum Action {
 }
 
 impl Action {
-	pub fn new(act: &str, val: &str, ability: NamedID, dst: &Option<Actor>) -> Self {
+	// None if the value can't be parsed
+	pub fn new(act: &str, val: &str, ability: NamedID, dst: &Option<Actor>) -> Option<Self> {
 		let mut parts = act.splitn(2, ':');
-		let event = NamedID::new(parts.next().unwrap());
-		let neffect = parts.next().unwrap();
+		let event = NamedID::new(parts.next().unwrap_or_default());
+		let neffect = parts.next().unwrap_or_default();
 		let effect = NamedID::new(neffect);
-		let val = Value::from(val);
+		let val = Value::from(val)?;
 
 		//dbg!(&val);
 		let is_max_health = move || -> bool {
@@ -120,19 +236,29 @@ impl Action {
 			}
 		};
 
-		match event.id {
-			SPEND => Self::Spend,
-			RESTORE => Self::Restore,
+		let action = match event.id {
+			SPEND => Self::Spend {
+				kind: ResourceKind::new(&effect),
+				value: val.total,
+			},
+			RESTORE => Self::Restore {
+				kind: ResourceKind::new(&effect),
+				value: val.total,
+			},
 
 			DISCIPLINE_CHANGED => {
 				// DisciplineChanged
 				let mut parts = neffect.split('/');
-				let class = NamedID::new(parts.next().unwrap());
-				let spec = NamedID::new(parts.next().unwrap());
+				let class = NamedID::new(parts.next().unwrap_or_default());
+				let spec = NamedID::new(parts.next().unwrap_or_default());
 				Self::DisciplineChanged { class, spec }
 			}
 
-			AREA_ENTERED => Self::AreaEntered(effect), // AreaEntered
+			AREA_ENTERED => Self::AreaEntered {
+				// Dxun - The CI-004 Facility {833571547775792} 8 Player Veteran {836045448953652}
+				difficulty: Difficulty::new(neffect.split_once('}').map_or("", |(_, d)| d)),
+				area: effect,
+			},
 
 			EVENT => match effect.id {
 				// event
@@ -198,7 +324,8 @@ impl Action {
 				effect,
 				event,
 			},
-		}
+		};
+		Some(action)
 	}
 }
 
@@ -215,7 +342,7 @@ struct Value {
 }
 
 impl Value {
-	fn from(p: &str) -> Self {
+	fn from(p: &str) -> Option<Self> {
 		let parts = p
 			.split(' ')
 			.map(|p| p.trim_matches(|c| c == '(' || c == ')'));
@@ -240,26 +367,26 @@ impl Value {
 				got_value = true;
 				total = if part.ends_with('*') {
 					critical = true;
-					part.strip_suffix('*').unwrap_or("0").parse().unwrap_or(0)
+					parse_amount(part.strip_suffix('*').unwrap_or("0"))
 				} else {
-					part.parse().unwrap_or(0)
+					parse_amount(part)
 				};
 			} else if let Some(v) = part.strip_prefix('~') {
-				tilde = v.parse().unwrap();
+				tilde = v.parse().ok()?;
 			} else if part.starts_with('{') && value_id == 0 {
-				value_id = part[1..part.rfind('}').unwrap()].parse().unwrap();
+				value_id = part.get(1..part.rfind('}')?)?.parse().ok()?;
 			} else if part == SHIELD_STR {
 				shielded = true;
 			} else if part == REFLECTED_STR {
 				reflected = true;
 			} else if got_value && absorbed == 0 && part.starts_with(|c: char| c.is_ascii_digit()) {
-				absorbed = part.parse().unwrap()
-			} else if part.starts_with('<') {
-				threat = part[1..part.len() - 1].parse().unwrap_or(0);
+				absorbed = part.parse().ok()?;
+			} else if let Some(v) = part.strip_prefix('<') {
+				threat = v.strip_suffix('>')?.parse().unwrap_or(0);
 			}
 		}
 
-		Self {
+		Some(Self {
 			value_id,
 			total,
 			absorbed,
@@ -268,6 +395,51 @@ impl Value {
 			critical,
 			shielded,
 			reflected,
-		}
+		})
+	}
+}
+
+// resources are sometimes logged with a fractional part (e.g. `(2.5)`)
+fn parse_amount(p: &str) -> i32 {
+	p.parse()
+		.unwrap_or_else(|_| p.parse::<f64>().map_or(0, |v| v.round() as i32))
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	#[test]
+	fn resources() {
+		let spend = Action::new(
+			"Spend {836045448945473}: energy {836045448938503}",
+			"(10)",
+			NamedID::default(),
+			&None,
+		)
+		.unwrap();
+		assert!(matches!(
+			spend,
+			Action::Spend {
+				kind: ResourceKind::Energy,
+				value: 10
+			}
+		));
+
+		// the id decides, not the (localized) name
+		let restore = Action::new(
+			"Restore {836045448945476}: Hitze {836045448938505}",
+			"(2.5)",
+			NamedID::default(),
+			&None,
+		)
+		.unwrap();
+		assert!(matches!(
+			restore,
+			Action::Restore {
+				kind: ResourceKind::Heat,
+				value: 3
+			}
+		));
 	}
 }
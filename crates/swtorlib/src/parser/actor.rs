@@ -6,6 +6,7 @@ use super::{
 pub type Direction = f64;
 
 #[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Position {
 	pub x: f64,
 	pub y: f64,
@@ -16,20 +17,23 @@ pub struct Position {
 impl Position {
 	pub fn new(p: &str) -> Self {
 		//dbg!(p);
-		let mut pos = p[1..p.len() - 1]
+		let mut pos = p
+			.trim_matches(|c| c == '(' || c == ')')
 			.splitn(4, ',')
 			.map(|n| n.parse::<f64>().unwrap_or(0.));
+		let mut next = || pos.next().unwrap_or(0.);
 
 		Self {
-			x: pos.next().unwrap(),
-			y: pos.next().unwrap(),
-			z: pos.next().unwrap(),
-			dir: pos.next().unwrap(),
+			x: next(),
+			y: next(),
+			z: next(),
+			dir: next(),
 		}
 	}
 }
 
 #[derive(Debug, Clone, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ActorType {
 	#[default]
 	Player,
@@ -38,6 +42,7 @@ pub enum ActorType {
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Actor {
 	pub id: NamedID,
 	pub typ: ActorType,
@@ -55,17 +60,17 @@ impl Actor {
 		}
 
 		let mut parts = p.split('|').map(|s| s.trim());
-		let mut name = parts.next().unwrap().trim();
+		let mut name = parts.next()?.trim();
 		let id: u64;
 		let mut typ = ActorType::Player;
 		if let Some(idx) = name.find('#') {
 			id = if let Some(sidx) = name.rfind('/') {
 				typ = ActorType::Companion(NamedID::new(&name[sidx + 1..]));
-				name[idx + 1..sidx].parse().unwrap()
+				name.get(idx + 1..sidx)?.parse().ok()?
 			} else {
-				name[idx + 1..].parse().unwrap()
+				name[idx + 1..].parse().ok()?
 			};
-			name = &name[1..idx];
+			name = name.get(1..idx)?;
 		} else {
 			typ = ActorType::NPC;
 			id = extract_num(p, '{', '}', false);
@@ -73,13 +78,12 @@ impl Actor {
 		};
 
 		// let pos = Position::new(parts.next().unwrap());
-		let pos = parts.next().unwrap().to_owned();
+		let pos = parts.next()?.to_owned();
 
-		let health = parts.next().unwrap();
-		let mut health = health
+		let (health, max_health) = parts
+			.next()?
 			.trim_matches(|c| c == '(' || c == ')')
-			.splitn(2, '/')
-			.map(|v| v.parse::<i32>().unwrap());
+			.split_once('/')?;
 
 		Some(Actor {
 			id: NamedID {
@@ -88,8 +92,8 @@ impl Actor {
 			},
 			typ,
 			// local_player: false,
-			health: health.next().unwrap(),
-			max_health: health.next().unwrap(),
+			health: health.parse().ok()?,
+			max_health: max_health.parse().ok()?,
 			pos,
 		})
 	}
@@ -1,10 +1,20 @@
-use std::ops::AddAssign;
+use std::{collections::VecDeque, ops::AddAssign};
+
+use chrono::{NaiveTime, Timelike};
 
 use crate::parser::utils::fmt_num;
 
-use super::{action::Action, actor::Actor, namedid::NamedID, sorted_vec::SortedVec};
+use super::{
+	action::{Action, ResourceKind},
+	actor::{Actor, ActorType},
+	discipline::{Class, Discipline},
+	namedid::NamedID,
+	role::Role,
+	sorted_vec::SortedVec,
+};
 
 #[derive(Debug, Clone, Default, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Meter {
 	pub id: NamedID,
 
@@ -39,6 +49,25 @@ impl Meter {
 		(self.casts as f64 / (seconds as f64 / 60.0)) as i32 as f64
 	}
 
+	pub fn crit_pct(&self) -> f64 {
+		if self.casts == 0 {
+			return 0.;
+		}
+		100. * (self.crits as f64 / self.casts as f64)
+	}
+
+	pub fn to_row(&self, seconds: i64) -> MeterRow {
+		MeterRow {
+			name: self.id.name.clone(),
+			casts: self.casts,
+			total: self.total,
+			crits: self.crits,
+			crit_pct: self.crit_pct(),
+			apm: self.apm(seconds),
+			xps: self.xps(seconds),
+		}
+	}
+
 	pub fn to_vec(&self, seconds: i64) -> Vec<String> {
 		let crit = 100. * (self.crits as f64 / self.casts as f64);
 		vec![
@@ -53,6 +82,19 @@ impl Meter {
 	}
 }
 
+// the raw numbers behind Meter::to_vec
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MeterRow {
+	pub name: String,
+	pub casts: i32,
+	pub total: i32,
+	pub crits: i32,
+	pub crit_pct: f64,
+	pub apm: f64,
+	pub xps: f64,
+}
+
 impl AddAssign<&Meter> for Meter {
 	fn add_assign(&mut self, other: &Self) {
 		self.casts += other.casts;
@@ -78,15 +120,102 @@ impl AddAssign<&Meter> for Meter {
 // 	}
 // }
 
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResourceMeter {
+	pub kind: ResourceKind,
+
+	pub spent: i32,
+	pub restored: i32,
+
+	// running balance (restored - spent) over the encounter, one point per spend/restore.
+	// it's relative to the start of the fight, not the level of the resource
+	pub balance_curve: Vec<(NaiveTime, i32)>,
+}
+
+impl ResourceMeter {
+	pub fn new(kind: ResourceKind) -> Self {
+		Self {
+			kind,
+			..Self::default()
+		}
+	}
+
+	pub fn update(&mut self, ts: NaiveTime, value: i32) {
+		if value < 0 {
+			self.spent -= value;
+		} else {
+			self.restored += value;
+		}
+		self.balance_curve.push((ts, self.balance()));
+	}
+
+	pub fn balance(&self) -> i32 {
+		self.restored - self.spent
+	}
+
+	// the lowest point of the balance, a big negative number means the player was starved
+	// (or overheated for heat based classes)
+	pub fn min_balance(&self) -> i32 {
+		self.balance_curve
+			.iter()
+			.map(|(_, v)| *v)
+			.min()
+			.unwrap_or(0)
+	}
+
+	pub fn max_balance(&self) -> i32 {
+		self.balance_curve
+			.iter()
+			.map(|(_, v)| *v)
+			.max()
+			.unwrap_or(0)
+	}
+
+	pub fn to_vec(&self) -> Vec<String> {
+		vec![
+			self.kind.name().to_owned(),
+			fmt_num(self.spent as f64),
+			fmt_num(self.restored as f64),
+			fmt_num(self.min_balance() as f64),
+			fmt_num(self.max_balance() as f64),
+		]
+	}
+}
+
+// how many incoming hits and heals a death recap keeps
+pub const RECAP_LEN: usize = 10;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Incoming {
+	pub ts: NaiveTime,
+	pub source: String,
+	pub ability: String,
+	pub value: i32,
+	pub critical: bool,
+	pub heal: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeathRecap {
+	pub ts: NaiveTime,
+	// oldest first, the killing blow is usually the last damage entry
+	pub events: Vec<Incoming>,
+}
+
 fn new_sorted_by_total() -> SortedVec<Meter> {
 	SortedVec::<Meter>::new(|a, b| a.total.cmp(&b.total))
 }
 
 #[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ActorStats {
 	pub id: NamedID,
 	pub spec: NamedID,
 	pub class: NamedID,
+	pub typ: ActorType,
 
 	pub max_health: i32,
 	pub health: i32,
@@ -102,10 +231,23 @@ pub struct ActorStats {
 	pub spells_out: SortedVec<Meter>,
 	pub spells_in: SortedVec<Meter>,
 
+	pub resources: Vec<ResourceMeter>,
+
+	// damage done in each second of the log clock that had any, for per second charts
+	#[cfg_attr(feature = "serde", serde(skip))]
+	pub dmg_curve: Vec<(NaiveTime, i32)>,
+	// the last RECAP_LEN hits and heals taken
+	pub recent_in: VecDeque<Incoming>,
+	pub death_recaps: Vec<DeathRecap>,
+
 	pub interrupted: i32,
 	pub absorbed: i32,
 	pub deaths: i32,
 	pub revives: i32,
+
+	// companion damage and healing counts as the owner's, their abilities are listed as
+	// "Ability (Companion)"
+	pub merge_companions: bool,
 }
 
 impl ActorStats {
@@ -122,7 +264,32 @@ impl ActorStats {
 		}
 	}
 
-	pub fn update(&mut self, src: &Option<Actor>, dst: &Option<Actor>, act: &Action) {
+	pub fn update(
+		&mut self,
+		ts: NaiveTime,
+		src: &Option<Actor>,
+		dst: &Option<Actor>,
+		act: &Action,
+	) {
+		for a in [src, dst].into_iter().flatten() {
+			if a.get_id() == self.id {
+				self.typ = a.typ.clone();
+				self.health = a.health;
+				self.max_health = a.max_health;
+			}
+		}
+
+		let ability_of = |src: &Option<Actor>, ability: &NamedID| match src {
+			Some(Actor {
+				typ: ActorType::Companion(c),
+				..
+			}) if self.merge_companions => NamedID {
+				id: ability.id,
+				name: format!("{} ({})", ability.name, c.name),
+			},
+			_ => ability.clone(),
+		};
+
 		match act {
 			Action::DisciplineChanged { class, spec } => {
 				self.class = class.clone();
@@ -142,9 +309,18 @@ impl ActorStats {
 
 				let m = &mut self.dmg_total;
 				m.update(*value, *critical);
-				let (dm, sm) = if src.clone().is_some_and(|src| src.get_id() == self.id) {
-					self.max_health = src.clone().unwrap().max_health;
-					self.health = src.clone().unwrap().health;
+				let ability = ability_of(src, ability);
+				let out = src.as_ref().is_some_and(|src| self.is_self(src));
+				if out {
+					let sec = ts.with_nanosecond(0).unwrap_or(ts);
+					match self.dmg_curve.last_mut() {
+						Some((t, v)) if *t == sec => *v += value,
+						_ => self.dmg_curve.push((sec, *value)),
+					}
+				} else {
+					self.push_incoming(ts, src, &ability, *value, *critical, false);
+				}
+				let (dm, sm) = if out {
 					(&mut self.dmg_out, &mut self.spells_out)
 				} else {
 					(&mut self.dmg_in, &mut self.spells_in)
@@ -159,7 +335,7 @@ impl ActorStats {
 				if id.id > 0 {
 					Self::update_meter(dm, id, |m| m.update(*value, *critical));
 				}
-				Self::update_meter(sm, ability.clone(), |m| m.update(*value, *critical));
+				Self::update_meter(sm, ability, |m| m.update(*value, *critical));
 			}
 			Action::Heal {
 				ability,
@@ -174,9 +350,13 @@ impl ActorStats {
 				let m = &mut self.heal_total;
 				m.update(*value, *critical);
 
-				let (dm, sm) = if src.clone().is_some_and(|src| src.get_id() == self.id) {
-					self.max_health = src.clone().unwrap().max_health;
-					self.health = src.clone().unwrap().health;
+				let ability = ability_of(src, ability);
+				let out = src.as_ref().is_some_and(|src| self.is_self(src));
+				let to_self = dst.as_ref().is_some_and(|dst| self.is_self(dst));
+				if to_self {
+					self.push_incoming(ts, src, &ability, *value, *critical, true);
+				}
+				let (dm, sm) = if out {
 					(&mut self.heal_out, &mut self.spells_out)
 				} else {
 					(&mut self.heal_in, &mut self.spells_in)
@@ -185,17 +365,138 @@ impl ActorStats {
 				if let Some(dst) = dst {
 					Self::update_meter(dm, dst.get_id(), |m| m.update(*value, *critical));
 				}
-				Self::update_meter(sm, ability.clone(), |m| m.update(*value, *critical));
+				Self::update_meter(sm, ability, |m| m.update(*value, *critical));
+			}
+
+			Action::Spend { kind, value } | Action::Restore { kind, value } => {
+				if !src.clone().is_some_and(|src| src.get_id() == self.id) {
+					return;
+				}
+				let value = if matches!(act, Action::Spend { .. }) {
+					-value
+				} else {
+					*value
+				};
+				self.update_resource(*kind, |m| m.update(ts, value));
 			}
 
-			Action::Death => self.deaths += 1,
-			Action::Revived => self.revives += 1,
+			// the target is the one dying (or the source if there is no target), not the killer
+			Action::Death | Action::Revived => {
+				if !dst
+					.as_ref()
+					.or(src.as_ref())
+					.is_some_and(|a| a.get_id() == self.id)
+				{
+					return;
+				}
+				if matches!(act, Action::Death) {
+					self.deaths += 1;
+					self.death_recaps.push(DeathRecap {
+						ts,
+						events: self.recent_in.drain(..).collect(),
+					});
+				} else {
+					self.revives += 1;
+				}
+			}
 			Action::Interrupted(_) => self.interrupted += 1,
 
 			_ => {}
 		}
 	}
 
+	pub fn role(&self) -> Role {
+		Role::from_spec(&self.spec)
+	}
+
+	pub fn discipline(&self) -> Option<&'static Discipline> {
+		Discipline::find(&self.spec)
+	}
+
+	pub fn class_info(&self) -> Option<&'static Class> {
+		Discipline::find(&self.spec)
+			.map(|d| d.class())
+			.or_else(|| Class::find(&self.class))
+	}
+
+	// canonical english name of the discipline, falls back to the logged (localized) name
+	pub fn spec_name(&self) -> String {
+		self.discipline()
+			.map_or_else(|| self.spec.name.clone(), |d| d.name.to_owned())
+	}
+
+	pub fn resource(&self, kind: ResourceKind) -> Option<&ResourceMeter> {
+		self.resources.iter().find(|m| m.kind == kind)
+	}
+
+	pub fn resources_to_vec(&self) -> Vec<(Vec<String>, f64)> {
+		self.resources
+			.iter()
+			.map(|m| (m.to_vec(), m.spent as f64))
+			.collect()
+	}
+
+	fn update_resource<F: Fn(&mut ResourceMeter)>(&mut self, kind: ResourceKind, process: F) {
+		if let Some(m) = self.resources.iter_mut().find(|m| m.kind == kind) {
+			process(m);
+		} else {
+			let mut m = ResourceMeter::new(kind);
+			process(&mut m);
+			self.resources.push(m);
+		}
+	}
+
+	// damage done in each second since start
+	pub fn dps_series(&self, start: NaiveTime, seconds: i64) -> Vec<i32> {
+		let start = start.with_nanosecond(0).unwrap_or(start);
+		let mut out = vec![0; seconds.max(0) as usize + 1];
+		for (ts, v) in &self.dmg_curve {
+			let i = (*ts - start).num_seconds();
+			if let Some(s) = usize::try_from(i).ok().and_then(|i| out.get_mut(i)) {
+				*s += v;
+			}
+		}
+		out
+	}
+
+	fn push_incoming(
+		&mut self,
+		ts: NaiveTime,
+		src: &Option<Actor>,
+		ability: &NamedID,
+		value: i32,
+		critical: bool,
+		heal: bool,
+	) {
+		if self.recent_in.len() == RECAP_LEN {
+			self.recent_in.pop_front();
+		}
+		self.recent_in.push_back(Incoming {
+			ts,
+			source: src.as_ref().map(|a| a.id.name.clone()).unwrap_or_default(),
+			ability: ability.name.clone(),
+			value,
+			critical,
+			heal,
+		});
+	}
+
+	// also true for the actor's companion when merging companions
+	pub fn is_self(&self, a: &Actor) -> bool {
+		a.get_id() == self.id || (self.merge_companions && a.is_companion() && a.id == self.id)
+	}
+
+	pub fn health_pct(&self) -> f64 {
+		if self.max_health == 0 {
+			return 0.;
+		}
+		100. * self.health as f64 / self.max_health as f64
+	}
+
+	pub fn is_player(&self) -> bool {
+		matches!(self.typ, ActorType::Player)
+	}
+
 	pub fn is_dead(&self) -> bool {
 		self.deaths > self.revives
 	}
@@ -242,6 +543,12 @@ impl ActorStats {
 		Self::all_to_vec(&self.heal_out, seconds)
 	}
 
+	pub fn meter_rows(v: &SortedVec<Meter>, seconds: i64) -> Vec<MeterRow> {
+		let mut out = v.iter().map(|m| m.to_row(seconds)).collect::<Vec<_>>();
+		out.sort_by_key(|r| std::cmp::Reverse(r.total));
+		out
+	}
+
 	fn all_to_vec(v: &SortedVec<Meter>, seconds: i64) -> Vec<(Vec<String>, f64)> {
 		let mut out = Vec::new();
 		for mm in v.iter() {
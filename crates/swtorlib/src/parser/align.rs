@@ -0,0 +1,146 @@
+use std::{
+	collections::{hash_map::DefaultHasher, HashMap},
+	hash::{Hash, Hasher},
+};
+
+use chrono::Duration;
+
+use super::{
+	merge::{around_midnight, signature},
+	*,
+};
+
+// skews larger than this are not considered
+const MAX_SKEW_SECS: i64 = 300;
+// offsets are voted on in buckets this wide
+const BUCKET_MS: i64 = 50;
+// events seen more often than this in a log are too ambiguous to pair up
+const MAX_REPEATS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Offset {
+	// add to the other log's timestamps to get the reference's clock
+	pub offset: Duration,
+	// how many shared events agreed on it
+	pub votes: usize,
+}
+
+// events both players should have logged at the same moment: npc deaths, entering the
+// same area and hits with the same source, target, ability and value
+fn anchors(log: &[Line]) -> HashMap<u64, Vec<&Line>> {
+	let mut out = HashMap::<u64, Vec<&Line>>::new();
+	for l in log {
+		let key = match &l.action {
+			Action::AreaEntered { area, .. } => area_signature(area),
+			Action::Death if l.target.as_ref().is_some_and(|t| t.is_npc()) => signature(l),
+			Action::Damage { value, .. } if *value > 0 => signature(l),
+			_ => continue,
+		};
+		out.entry(key).or_default().push(l);
+	}
+	out.retain(|_, v| v.len() <= MAX_REPEATS);
+	out
+}
+
+// the source of AreaEntered is the log's owner, only the area is shared
+fn area_signature(area: &NamedID) -> u64 {
+	let mut h = DefaultHasher::new();
+	area.hash(&mut h);
+	h.finish()
+}
+
+// estimates the clock skew of other against reference from the events they share
+pub fn estimate(reference: &[Line], other: &[Line]) -> Option<Offset> {
+	let max = Duration::seconds(MAX_SKEW_SECS);
+	let theirs = anchors(other);
+	let mut diffs = Vec::new();
+	for (key, ours) in anchors(reference) {
+		let Some(theirs) = theirs.get(&key) else {
+			continue;
+		};
+		for a in &ours {
+			for b in theirs {
+				let d = around_midnight(a.ts - b.ts);
+				if d.abs() <= max {
+					diffs.push(d.num_milliseconds());
+				}
+			}
+		}
+	}
+
+	// the bucket with the most votes, then the mean of the votes around it
+	let mut buckets = HashMap::<i64, usize>::new();
+	for d in &diffs {
+		*buckets.entry(d.div_euclid(BUCKET_MS)).or_default() += 1;
+	}
+	let (bucket, _) = buckets.into_iter().max_by_key(|(b, n)| (*n, -b.abs()))?;
+	let center = bucket * BUCKET_MS + BUCKET_MS / 2;
+	let close = diffs
+		.iter()
+		.filter(|d| (**d - center).abs() <= BUCKET_MS * 2)
+		.collect::<Vec<_>>();
+	let mean = close.iter().copied().sum::<i64>() / close.len() as i64;
+
+	Some(Offset {
+		offset: Duration::milliseconds(mean),
+		votes: close.len(),
+	})
+}
+
+pub fn apply(log: &mut [Line], offset: Duration) {
+	for l in log {
+		l.ts += offset;
+	}
+}
+
+// shifts every log onto the first log's clock, returns the offsets used
+pub fn align(logs: &mut [Vec<Line>]) -> Vec<Option<Offset>> {
+	let Some((reference, others)) = logs.split_first_mut() else {
+		return Vec::new();
+	};
+	others
+		.iter_mut()
+		.map(|log| {
+			let o = estimate(reference, log);
+			if let Some(o) = o {
+				apply(log, o.offset);
+			}
+			o
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	fn lines(log: &str) -> Vec<Line> {
+		log.lines().map(|l| Line::new(l).unwrap()).collect()
+	}
+
+	#[test]
+	fn skew() {
+		let tank = lines("\
+[20:00:02.000] [@Tank#1|(0,0,0,0)|(100/100)] [Dread Guard {3273941900591105}:1|(0,0,0,0)|(770/1000)] [Strike {1}] [ApplyEffect {836045448945477}: Damage {836045448945501}] (230 kinetic {836045448940873})
+[20:00:03.000] [@Tank#1|(0,0,0,0)|(100/100)] [Dread Guard {3273941900591105}:1|(0,0,0,0)|(0/1000)] [Strike {1}] [ApplyEffect {836045448945477}: Damage {836045448945501}] (770 kinetic {836045448940873})
+[20:00:03.010] [@Tank#1|(0,0,0,0)|(100/100)] [Dread Guard {3273941900591105}:1|(0,0,0,0)|(0/1000)] [] [Event {836045448945472}: Death {836045448945493}]
+[20:00:09.000] [@Tank#1|(0,0,0,0)|(100/100)] [Dread Guard {3273941900591105}:2|(0,0,0,0)|(900/1000)] [Strike {1}] [ApplyEffect {836045448945477}: Damage {836045448945501}] (100 kinetic {836045448940873})
+");
+		// 3.2 seconds behind, with a hit of its own that happens to match one of the tank's
+		let heals = lines("\
+[19:59:58.800] [@Tank#1|(0,0,0,0)|(100/100)] [Dread Guard {3273941900591105}:1|(0,0,0,0)|(770/1000)] [Strike {1}] [ApplyEffect {836045448945477}: Damage {836045448945501}] (230 kinetic {836045448940873})
+[19:59:59.820] [@Tank#1|(0,0,0,0)|(100/100)] [Dread Guard {3273941900591105}:1|(0,0,0,0)|(0/1000)] [Strike {1}] [ApplyEffect {836045448945477}: Damage {836045448945501}] (770 kinetic {836045448940873})
+[19:59:59.810] [@Tank#1|(0,0,0,0)|(100/100)] [Dread Guard {3273941900591105}:1|(0,0,0,0)|(0/1000)] [] [Event {836045448945472}: Death {836045448945493}]
+[20:00:09.000] [@Tank#1|(0,0,0,0)|(100/100)] [Dread Guard {3273941900591105}:2|(0,0,0,0)|(900/1000)] [Strike {1}] [ApplyEffect {836045448945477}: Damage {836045448945501}] (100 kinetic {836045448940873})
+");
+
+		let o = estimate(&tank, &heals).unwrap();
+		assert_eq!(o.votes, 3);
+		assert_eq!(o.offset.num_milliseconds(), 3193);
+
+		let mut logs = vec![tank, heals];
+		align(&mut logs);
+		assert_eq!(logs[1][2].ts, logs[0][2].ts - Duration::milliseconds(7));
+		assert!(estimate(&logs[0], &[]).is_none());
+	}
+}
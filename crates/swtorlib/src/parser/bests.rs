@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+
+use super::actor_stats::ActorStats;
+use super::encounter::{Encounter, Outcome};
+
+// personal bests are tracked per boss, difficulty, character and discipline
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+pub struct Key {
+	pub boss: String,
+	pub difficulty: String,
+	pub character: String,
+	pub discipline: String,
+}
+
+impl Key {
+	// None for trash
+	pub fn new(e: &Encounter, p: &ActorStats) -> Option<Self> {
+		Some(Self {
+			boss: e.boss_name()?,
+			difficulty: e.difficulty.to_string(),
+			character: p.id.name.clone(),
+			discipline: p.spec_name(),
+		})
+	}
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Record {
+	pub dps: f64,
+	pub hps: f64,
+}
+
+impl Record {
+	pub fn new(e: &Encounter, p: &ActorStats) -> Self {
+		// a zero length encounter would make a best nothing can beat
+		let elapsed = e.elapsed().num_seconds().max(1);
+		Self {
+			dps: p.all_dmg_out().xps(elapsed),
+			hps: p.all_heal_out().xps(elapsed),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Comparison {
+	pub current: Record,
+	pub best: Option<Record>,
+	pub previous: Option<Record>,
+}
+
+impl Comparison {
+	// deltas are in percent, None when there is nothing to compare against
+	pub fn dps_vs_best(&self) -> Option<f64> {
+		delta(self.current.dps, self.best?.dps)
+	}
+
+	pub fn hps_vs_best(&self) -> Option<f64> {
+		delta(self.current.hps, self.best?.hps)
+	}
+
+	pub fn dps_vs_previous(&self) -> Option<f64> {
+		delta(self.current.dps, self.previous?.dps)
+	}
+
+	pub fn hps_vs_previous(&self) -> Option<f64> {
+		delta(self.current.hps, self.previous?.hps)
+	}
+}
+
+fn delta(cur: f64, other: f64) -> Option<f64> {
+	if other > 0. && cur.is_finite() {
+		Some(100. * (cur - other) / other)
+	} else {
+		None
+	}
+}
+
+// only kills count as personal bests, the previous pull can be any outcome
+#[derive(Debug, Clone, Default)]
+pub struct Bests {
+	best: HashMap<Key, Record>,
+	previous: HashMap<Key, Record>,
+}
+
+impl Bests {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	// encounters must be added oldest first
+	pub fn add(&mut self, e: &Encounter) {
+		let kill = e.outcome() == Outcome::Kill;
+		for p in e.players.iter() {
+			if let Some(key) = Key::new(e, p) {
+				self.add_record(key, Record::new(e, p), kill);
+			}
+		}
+	}
+
+	pub fn add_record(&mut self, key: Key, r: Record, kill: bool) {
+		if kill {
+			let best = self.best.entry(key.clone()).or_default();
+			best.dps = best.dps.max(r.dps);
+			best.hps = best.hps.max(r.hps);
+		}
+		self.previous.insert(key, r);
+	}
+
+	pub fn best(&self, key: &Key) -> Option<&Record> {
+		self.best.get(key)
+	}
+
+	pub fn previous(&self, key: &Key) -> Option<&Record> {
+		self.previous.get(key)
+	}
+
+	// compares p in e, which should not have been added yet
+	pub fn compare(&self, e: &Encounter, p: &ActorStats) -> Option<Comparison> {
+		let key = Key::new(e, p)?;
+		Some(Comparison {
+			current: Record::new(e, p),
+			best: self.best(&key).copied(),
+			previous: self.previous(&key).copied(),
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	use crate::{
+		fixtures::BRONTES,
+		parser::{encounter::Encounters, line::Line},
+	};
+
+	#[test]
+	fn best_and_previous() {
+		let key = Key {
+			boss: "Brontes".to_owned(),
+			difficulty: "8m Veteran".to_owned(),
+			character: "Tank".to_owned(),
+			discipline: "Immortal".to_owned(),
+		};
+		let mut b = Bests::new();
+		b.add_record(
+			key.clone(),
+			Record {
+				dps: 5000.,
+				hps: 0.,
+			},
+			true,
+		);
+		b.add_record(
+			key.clone(),
+			Record {
+				dps: 8000.,
+				hps: 0.,
+			},
+			false,
+		);
+		b.add_record(
+			key.clone(),
+			Record {
+				dps: 6000.,
+				hps: 0.,
+			},
+			true,
+		);
+
+		assert_eq!(b.best(&key).unwrap().dps, 6000.);
+		assert_eq!(b.previous(&key).unwrap().dps, 6000.);
+
+		let c = Comparison {
+			current: Record {
+				dps: 6600.,
+				hps: 0.,
+			},
+			best: b.best(&key).copied(),
+			previous: None,
+		};
+		assert_eq!(c.dps_vs_best().unwrap().round(), 10.);
+		assert!(c.hps_vs_best().is_none());
+		assert!(c.dps_vs_previous().is_none());
+	}
+
+	#[test]
+	fn by_discipline() {
+		let mut b = Bests::new();
+		for spec in ["Immortal", "Vengeance"] {
+			// logged when zoning in, before the fights
+			let discipline = format!("[20:00:00.500] [@Tank#1|(0,0,0,0)|(100/100)] [] [] [DisciplineChanged {{836045448953665}}: Juggernaut {{16141170711935532310}}/{spec} {{1}}]");
+			let mut encs = Encounters::new();
+			for l in BRONTES
+				.lines()
+				.take(1)
+				.chain([discipline.as_str()])
+				.chain(BRONTES.lines().skip(1))
+			{
+				encs.push(Line::new(l).unwrap(), &|_, _| {});
+			}
+			for e in encs.finished() {
+				b.add(&e);
+			}
+		}
+
+		let key = |discipline: &str| Key {
+			boss: "Brontes".to_owned(),
+			difficulty: "8m Veteran".to_owned(),
+			character: "Tank".to_owned(),
+			discipline: discipline.to_owned(),
+		};
+		assert_eq!(b.best(&key("Immortal")).unwrap().dps, 500.);
+		assert_eq!(b.best(&key("Vengeance")).unwrap().dps, 500.);
+		assert!(b.best(&key("")).is_none());
+	}
+}
@@ -0,0 +1,141 @@
+use std::sync::OnceLock;
+
+use super::namedid::NamedID;
+
+static BUILTIN: &str = include_str!("../../data/bosses.txt");
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Boss {
+	pub name: String,
+	pub area: NamedID,
+	pub npcs: Vec<NamedID>,
+}
+
+impl Boss {
+	pub fn has_npc(&self, id: &NamedID) -> bool {
+		self.npcs.iter().any(|n| same(n, id))
+	}
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Bosses(pub Vec<Boss>);
+
+impl Bosses {
+	pub fn builtin() -> &'static Self {
+		static INSTANCE: OnceLock<Bosses> = OnceLock::new();
+		INSTANCE.get_or_init(|| Self::parse(BUILTIN))
+	}
+
+	pub fn load(path: &str) -> std::io::Result<Self> {
+		Ok(Self::parse(&std::fs::read_to_string(path)?))
+	}
+
+	pub fn parse(data: &str) -> Self {
+		let mut out = Vec::new();
+		let mut area = NamedID::default();
+		for l in data.lines().map(|l| l.trim()) {
+			if l.is_empty() || l.starts_with('#') {
+				continue;
+			}
+
+			if let Some(l) = l.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+				area = NamedID::new(l);
+			} else if let Some((name, npcs)) = l.split_once('=') {
+				out.push(Boss {
+					name: name.trim().to_owned(),
+					area: area.clone(),
+					npcs: npcs.split('|').map(|n| NamedID::new(n.trim())).collect(),
+				});
+			}
+		}
+		Self(out)
+	}
+
+	// picks the boss with the most matching npcs, ties go to the fight with less npcs
+	// so Bestia alone is not mistaken for the Dread Council. an area the database doesn't
+	// know, e.g. one without an id on a non-english client, doesn't narrow the search
+	pub fn find<'a, I: Iterator<Item = &'a NamedID>>(
+		&self,
+		area: &NamedID,
+		npcs: I,
+	) -> Option<&Boss> {
+		let npcs = npcs.collect::<Vec<_>>();
+		let known = self.0.iter().any(|b| same(&b.area, area));
+		self.0
+			.iter()
+			.filter(|b| !known || same(&b.area, area))
+			.map(|b| (b, npcs.iter().filter(|n| b.has_npc(n)).count()))
+			.filter(|(_, n)| *n > 0)
+			.max_by(|(a, an), (b, bn)| an.cmp(bn).then(b.npcs.len().cmp(&a.npcs.len())))
+			.map(|(b, _)| b)
+	}
+}
+
+// db is the database's side, its name is only used when it has no id
+fn same(db: &NamedID, id: &NamedID) -> bool {
+	if db.id != 0 {
+		db.id == id.id
+	} else {
+		!db.name.is_empty() && db.name.eq_ignore_ascii_case(&id.name)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	#[test]
+	fn find_boss() {
+		let db = Bosses::builtin();
+		let area = NamedID::new("The Dread Palace {833571547775669}");
+		let bestia = NamedID::new("Dread Master Bestia {3273941900591104}");
+		let brontes = NamedID::new("Dread Master Brontes {3266825467691008}");
+
+		let b = db.find(&area, [&bestia].into_iter()).unwrap();
+		assert_eq!(b.name, "Bestia");
+
+		let b = db.find(&area, [&bestia, &brontes].into_iter()).unwrap();
+		assert_eq!(b.name, "Dread Council");
+
+		let area = NamedID::new("The Dread Fortress {833571547775668}");
+		let b = db.find(&area, [&brontes].into_iter()).unwrap();
+		assert_eq!(b.name, "Brontes");
+
+		let trash = NamedID::new("Dread Guard {3273941900591105}");
+		assert!(db.find(&area, [&trash].into_iter()).is_none());
+	}
+
+	#[test]
+	fn ids_before_names() {
+		let db = Bosses::parse(
+			"
+[The Dread Fortress {833571547775668}]
+Brontes = Dread Master Brontes {3266825467691008}
+Draxus = Gate Commander Draxus
+",
+		);
+		// a german client
+		let area = NamedID::new("Die Schreckensfestung {833571547775668}");
+		let brontes = NamedID::new("Schreckensmeisterin Brontes {3266825467691008}");
+		assert_eq!(
+			db.find(&area, [&brontes].into_iter()).unwrap().name,
+			"Brontes"
+		);
+
+		// the name doesn't help once the db has an id
+		let other = NamedID::new("Dread Master Brontes {3266825467691009}");
+		assert!(db.find(&area, [&other].into_iter()).is_none());
+
+		// unknown areas don't hide a boss matched by id
+		let area = NamedID::new("Unbekannt {1}");
+		assert_eq!(
+			db.find(&area, [&brontes].into_iter()).unwrap().name,
+			"Brontes"
+		);
+
+		// without an id only the english name matches
+		let draxus = NamedID::new("Torkommandant Draxus {3266825467691010}");
+		let area = NamedID::new("The Dread Fortress {833571547775668}");
+		assert!(db.find(&area, [&draxus].into_iter()).is_none());
+	}
+}
@@ -0,0 +1,202 @@
+use std::{
+	io,
+	path::{Path, PathBuf},
+	time::UNIX_EPOCH,
+};
+
+use chrono::NaiveDateTime;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{reader::read_lines, *};
+
+// what's in a log file, scanning one means parsing all of it so they are cached in an index
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LogInfo {
+	pub path: String,
+	pub name: String,
+	// from the file name, None if it's not named like the game names them
+	pub date: Option<NaiveDateTime>,
+	pub size: u64,
+	// seconds since the epoch
+	pub modified: u64,
+	// the characters that owned the log, in order of appearance
+	pub characters: Vec<String>,
+	pub areas: Vec<String>,
+	pub encounters: usize,
+}
+
+impl LogInfo {
+	pub fn scan(path: &Path) -> io::Result<Self> {
+		let mut info = Self::stat(path)?;
+		for l in read_lines(&info.path)? {
+			match &l.action {
+				Action::AreaEntered { area, .. } => {
+					if let Some(src) = l.source.as_ref().filter(|s| s.is_player()) {
+						push_new(&mut info.characters, &src.id.name);
+					}
+					push_new(&mut info.areas, &area.name);
+				}
+				Action::EnterCombat => info.encounters += 1,
+				_ => {}
+			}
+		}
+		Ok(info)
+	}
+
+	// everything but the contents
+	fn stat(path: &Path) -> io::Result<Self> {
+		let meta = std::fs::metadata(path)?;
+		let name = path
+			.file_name()
+			.map_or_else(String::new, |n| n.to_string_lossy().to_string());
+		Ok(Self {
+			path: path.display().to_string(),
+			date: log_time(&name),
+			name,
+			size: meta.len(),
+			modified: meta
+				.modified()?
+				.duration_since(UNIX_EPOCH)
+				.map_or(0, |d| d.as_secs()),
+			..Self::default()
+		})
+	}
+
+	fn unchanged(&self, other: &Self) -> bool {
+		self.path == other.path && self.size == other.size && self.modified == other.modified
+	}
+}
+
+fn push_new(v: &mut Vec<String>, s: &str) {
+	if !s.is_empty() && !v.iter().any(|x| x == s) {
+		v.push(s.to_owned());
+	}
+}
+
+// combat_2026-10-18_20_00_00_000000.txt
+pub fn log_time(name: &str) -> Option<NaiveDateTime> {
+	let ts = name.strip_prefix("combat_")?.get(..19)?;
+	NaiveDateTime::parse_from_str(ts, "%Y-%m-%d_%H_%M_%S").ok()
+}
+
+// the .txt files in dir, oldest first by the time in their name or when they were modified
+pub fn log_files(dir: &str) -> io::Result<Vec<PathBuf>> {
+	let mut files = Vec::new();
+	for entry in std::fs::read_dir(dir)? {
+		let path = entry?.path();
+		if path.is_file() && path.extension().is_some_and(|e| e == "txt") {
+			let name = path.file_name().unwrap_or_default().to_string_lossy();
+			let modified = path.metadata().and_then(|m| m.modified()).ok();
+			files.push(((log_time(&name), modified), path));
+		}
+	}
+	files.sort();
+	Ok(files.into_iter().map(|(_, p)| p).collect())
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Catalog {
+	// newest first
+	pub logs: Vec<LogInfo>,
+}
+
+impl Catalog {
+	// only the files that changed since they were added to cached are scanned again
+	pub fn scan(dir: &str, cached: &Catalog) -> io::Result<Self> {
+		let mut logs = Vec::new();
+		for path in log_files(dir)?.iter().rev() {
+			let info = LogInfo::stat(path)?;
+			match cached.logs.iter().find(|c| c.unchanged(&info)) {
+				Some(c) => logs.push(c.clone()),
+				None => logs.push(LogInfo::scan(path)?),
+			}
+		}
+		Ok(Self { logs })
+	}
+
+	pub fn latest(&self) -> Option<&LogInfo> {
+		self.logs.first()
+	}
+}
+
+#[cfg(feature = "serde")]
+impl Catalog {
+	// <data dir>/swtormeter/logs.json
+	pub fn default_index_path() -> Option<String> {
+		let dir = dirs_next::data_dir()?.join("swtormeter");
+		Some(dir.join("logs.json").display().to_string())
+	}
+
+	pub fn load(path: &str) -> io::Result<Self> {
+		serde_json::from_slice(&std::fs::read(path)?)
+			.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+	}
+
+	pub fn save(&self, path: &str) -> io::Result<()> {
+		if let Some(dir) = Path::new(path).parent() {
+			std::fs::create_dir_all(dir)?;
+		}
+		let data = serde_json::to_vec(self).map_err(io::Error::other)?;
+		std::fs::write(path, data)
+	}
+
+	// scans dir using the default index, which is updated afterwards. a missing or broken
+	// index just means every file is scanned again
+	pub fn open(dir: &str) -> io::Result<Self> {
+		let index = Self::default_index_path();
+		let cached = index
+			.as_deref()
+			.and_then(|p| Self::load(p).ok())
+			.unwrap_or_default();
+		let catalog = Self::scan(dir, &cached)?;
+		if let Some(p) = index.filter(|_| catalog != cached) {
+			catalog.save(&p)?;
+		}
+		Ok(catalog)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	use crate::{fixtures::BRONTES, parser::reader::latest_log};
+
+	#[test]
+	fn scan_dir() {
+		let dir = std::env::temp_dir().join(format!("swtormeter-catalog-{}", std::process::id()));
+		let _ = std::fs::remove_dir_all(&dir);
+		std::fs::create_dir_all(&dir).unwrap();
+		let d = dir.to_str().unwrap();
+		assert!(latest_log(d).is_err());
+
+		let old = BRONTES;
+		std::fs::write(dir.join("combat_2026-10-17_20_00_00_000000.txt"), old).unwrap();
+		std::fs::write(dir.join("combat_2026-10-18_09_00_00_000000.txt"), "").unwrap();
+		std::fs::write(dir.join("notes.md"), "").unwrap();
+
+		let (_, name) = latest_log(d).unwrap();
+		assert_eq!(name, "combat_2026-10-18_09_00_00_000000.txt");
+
+		let c = Catalog::scan(d, &Catalog::default()).unwrap();
+		assert_eq!(c.logs.len(), 2);
+		assert_eq!(c.latest().unwrap().encounters, 0);
+		let info = &c.logs[1];
+		assert_eq!(info.date.unwrap().to_string(), "2026-10-17 20:00:00");
+		assert_eq!(info.size, old.len() as u64);
+		assert_eq!(info.characters, vec!["Tank"]);
+		assert_eq!(info.areas, vec!["The Dread Fortress"]);
+		assert_eq!(info.encounters, 2);
+
+		// unchanged files are taken from the cache as they are
+		let mut cached = c.clone();
+		cached.logs[1].encounters = 7;
+		assert_eq!(Catalog::scan(d, &cached).unwrap().logs[1].encounters, 7);
+
+		std::fs::remove_dir_all(&dir).unwrap();
+	}
+}
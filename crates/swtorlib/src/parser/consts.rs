@@ -19,6 +19,10 @@ pub const DISCIPLINE_CHANGED: u64 = 836045448953665;
 
 pub const CHARGES: u64 = 836045448953667;
 
+// the effect of Spend / Restore
+pub const RESOURCE_ENERGY: u64 = 836045448938503;
+pub const RESOURCE_HEAT: u64 = 836045448938505;
+
 pub const ENERGY: u64 = 836045448940874;
 pub const KINETIC: u64 = 836045448940873;
 pub const ELEMENTAL: u64 = 836045448940875;
@@ -0,0 +1,242 @@
+use super::{namedid::NamedID, role::Role};
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum Faction {
+	Empire,
+	Republic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Class {
+	pub id: u64,
+	pub name: &'static str,
+	pub base: &'static str,
+	pub faction: Faction,
+	pub mirror: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Discipline {
+	pub id: u64,
+	pub name: &'static str,
+	pub role: Role,
+	pub class: u64,
+	pub mirror: u64,
+}
+
+impl Class {
+	// the english name wins over the id, see DISCIPLINES
+	pub fn find(id: &NamedID) -> Option<&'static Self> {
+		CLASSES
+			.iter()
+			.find(|c| c.name == id.name || c.base == id.name)
+			.or_else(|| CLASSES.iter().find(|c| c.id == id.id))
+	}
+
+	pub fn mirror(&self) -> &'static Self {
+		CLASSES.iter().find(|c| c.id == self.mirror).unwrap()
+	}
+
+	pub fn disciplines(&self) -> impl Iterator<Item = &'static Discipline> {
+		let id = self.id;
+		DISCIPLINES.iter().filter(move |d| d.class == id)
+	}
+}
+
+impl Discipline {
+	// the english name wins over the id, see DISCIPLINES
+	pub fn find(id: &NamedID) -> Option<&'static Self> {
+		DISCIPLINES
+			.iter()
+			.find(|d| d.name == id.name)
+			.or_else(|| DISCIPLINES.iter().find(|d| d.id == id.id))
+	}
+
+	pub fn class(&self) -> &'static Class {
+		CLASSES.iter().find(|c| c.id == self.class).unwrap()
+	}
+
+	pub fn mirror(&self) -> &'static Self {
+		DISCIPLINES.iter().find(|d| d.id == self.mirror).unwrap()
+	}
+
+	pub fn faction(&self) -> Faction {
+		self.class().faction
+	}
+
+	// the same id for a discipline and its mirror, used to compare mirror classes side by side
+	pub fn pair_id(&self) -> u64 {
+		self.id.min(self.mirror)
+	}
+
+	// "Madness Sorcerer"
+	pub fn full_name(&self) -> String {
+		format!("{} {}", self.name, self.class().name)
+	}
+}
+
+macro_rules! class {
+	($id:ident, $name:literal, $base:literal, $faction:ident, $mirror:ident) => {
+		Class {
+			id: $id,
+			name: $name,
+			base: $base,
+			faction: Faction::$faction,
+			mirror: $mirror,
+		}
+	};
+}
+
+macro_rules! discipline {
+	($id:literal, $name:literal, $role:ident, $class:ident, $mirror:literal) => {
+		Discipline {
+			id: $id,
+			name: $name,
+			role: Role::$role,
+			class: $class,
+			mirror: $mirror,
+		}
+	};
+}
+
+const JUGGERNAUT: u64 = 16141170711935532310;
+const MARAUDER: u64 = 16141111589108060476;
+const SORCERER: u64 = 16140905232405801950;
+const ASSASSIN: u64 = 16141179069172110958;
+const POWERTECH: u64 = 16140973599688231714;
+const MERCENARY: u64 = 16141010271067846579;
+const OPERATIVE: u64 = 16141186039516617318;
+const SNIPER: u64 = 16140999200281838339;
+const GUARDIAN: u64 = 16141067504602942620;
+const SENTINEL: u64 = 16140912704077491401;
+const SAGE: u64 = 16141105691795089171;
+const SHADOW: u64 = 16141122432003627581;
+const VANGUARD: u64 = 16141174025082811713;
+const COMMANDO: u64 = 16141119516274073244;
+const SCOUNDREL: u64 = 16141163438392504574;
+const GUNSLINGER: u64 = 16140911592116520003;
+
+pub static CLASSES: [Class; 16] = [
+	class!(JUGGERNAUT, "Juggernaut", "Sith Warrior", Empire, GUARDIAN),
+	class!(MARAUDER, "Marauder", "Sith Warrior", Empire, SENTINEL),
+	class!(SORCERER, "Sorcerer", "Sith Inquisitor", Empire, SAGE),
+	class!(ASSASSIN, "Assassin", "Sith Inquisitor", Empire, SHADOW),
+	class!(POWERTECH, "Powertech", "Bounty Hunter", Empire, VANGUARD),
+	class!(MERCENARY, "Mercenary", "Bounty Hunter", Empire, COMMANDO),
+	class!(OPERATIVE, "Operative", "Imperial Agent", Empire, SCOUNDREL),
+	class!(SNIPER, "Sniper", "Imperial Agent", Empire, GUNSLINGER),
+	class!(GUARDIAN, "Guardian", "Jedi Knight", Republic, JUGGERNAUT),
+	class!(SENTINEL, "Sentinel", "Jedi Knight", Republic, MARAUDER),
+	class!(SAGE, "Sage", "Jedi Consular", Republic, SORCERER),
+	class!(SHADOW, "Shadow", "Jedi Consular", Republic, ASSASSIN),
+	class!(VANGUARD, "Vanguard", "Trooper", Republic, POWERTECH),
+	class!(COMMANDO, "Commando", "Trooper", Republic, MERCENARY),
+	class!(SCOUNDREL, "Scoundrel", "Smuggler", Republic, OPERATIVE),
+	class!(GUNSLINGER, "Gunslinger", "Smuggler", Republic, SNIPER),
+];
+
+// the ids haven't been checked against logs from the live game yet, any that turn out wrong
+// need fixing here. until they are find matches the english name first so an english client
+// is never grouped under a wrong id, other languages only match by id
+#[rustfmt::skip]
+pub static DISCIPLINES: [Discipline; 48] = [
+	// Juggernaut / Guardian
+	discipline!(2031339142381577, "Vengeance", Dps, JUGGERNAUT, 2031339142381529),
+	discipline!(2031339142381578, "Immortal", Tank, JUGGERNAUT, 2031339142381530),
+	discipline!(2031339142381579, "Rage", Dps, JUGGERNAUT, 2031339142381531),
+	discipline!(2031339142381529, "Vigilance", Dps, GUARDIAN, 2031339142381577),
+	discipline!(2031339142381530, "Defense", Tank, GUARDIAN, 2031339142381578),
+	discipline!(2031339142381531, "Focus", Dps, GUARDIAN, 2031339142381579),
+	// Marauder / Sentinel
+	discipline!(2031339142381580, "Annihilation", Dps, MARAUDER, 2031339142381532),
+	discipline!(2031339142381581, "Carnage", Dps, MARAUDER, 2031339142381533),
+	discipline!(2031339142381582, "Fury", Dps, MARAUDER, 2031339142381534),
+	discipline!(2031339142381532, "Watchman", Dps, SENTINEL, 2031339142381580),
+	discipline!(2031339142381533, "Combat", Dps, SENTINEL, 2031339142381581),
+	discipline!(2031339142381534, "Concentration", Dps, SENTINEL, 2031339142381582),
+	// Sorcerer / Sage
+	discipline!(2031339142381583, "Lightning", Dps, SORCERER, 2031339142381535),
+	discipline!(2031339142381584, "Madness", Dps, SORCERER, 2031339142381536),
+	discipline!(2031339142381585, "Corruption", Healer, SORCERER, 2031339142381537),
+	discipline!(2031339142381535, "Telekinetics", Dps, SAGE, 2031339142381583),
+	discipline!(2031339142381536, "Balance", Dps, SAGE, 2031339142381584),
+	discipline!(2031339142381537, "Seer", Healer, SAGE, 2031339142381585),
+	// Assassin / Shadow
+	discipline!(2031339142381586, "Hatred", Dps, ASSASSIN, 2031339142381538),
+	discipline!(2031339142381587, "Darkness", Tank, ASSASSIN, 2031339142381539),
+	discipline!(2031339142381588, "Deception", Dps, ASSASSIN, 2031339142381540),
+	discipline!(2031339142381538, "Serenity", Dps, SHADOW, 2031339142381586),
+	discipline!(2031339142381539, "Kinetic Combat", Tank, SHADOW, 2031339142381587),
+	discipline!(2031339142381540, "Infiltration", Dps, SHADOW, 2031339142381588),
+	// Powertech / Vanguard
+	discipline!(2031339142381589, "Shield Tech", Tank, POWERTECH, 2031339142381541),
+	discipline!(2031339142381590, "Pyrotech", Dps, POWERTECH, 2031339142381542),
+	discipline!(2031339142381591, "Advanced Prototype", Dps, POWERTECH, 2031339142381543),
+	discipline!(2031339142381541, "Shield Specialist", Tank, VANGUARD, 2031339142381589),
+	discipline!(2031339142381542, "Plasmatech", Dps, VANGUARD, 2031339142381590),
+	discipline!(2031339142381543, "Tactics", Dps, VANGUARD, 2031339142381591),
+	// Mercenary / Commando
+	discipline!(2031339142381592, "Arsenal", Dps, MERCENARY, 2031339142381544),
+	discipline!(2031339142381593, "Innovative Ordnance", Dps, MERCENARY, 2031339142381545),
+	discipline!(2031339142381594, "Bodyguard", Healer, MERCENARY, 2031339142381546),
+	discipline!(2031339142381544, "Gunnery", Dps, COMMANDO, 2031339142381592),
+	discipline!(2031339142381545, "Assault Specialist", Dps, COMMANDO, 2031339142381593),
+	discipline!(2031339142381546, "Combat Medic", Healer, COMMANDO, 2031339142381594),
+	// Operative / Scoundrel
+	discipline!(2031339142381595, "Concealment", Dps, OPERATIVE, 2031339142381547),
+	discipline!(2031339142381596, "Lethality", Dps, OPERATIVE, 2031339142381548),
+	discipline!(2031339142381597, "Medicine", Healer, OPERATIVE, 2031339142381549),
+	discipline!(2031339142381547, "Scrapper", Dps, SCOUNDREL, 2031339142381595),
+	discipline!(2031339142381548, "Ruffian", Dps, SCOUNDREL, 2031339142381596),
+	discipline!(2031339142381549, "Sawbones", Healer, SCOUNDREL, 2031339142381597),
+	// Sniper / Gunslinger
+	discipline!(2031339142381598, "Marksmanship", Dps, SNIPER, 2031339142381550),
+	discipline!(2031339142381599, "Engineering", Dps, SNIPER, 2031339142381551),
+	discipline!(2031339142381600, "Virulence", Dps, SNIPER, 2031339142381552),
+	discipline!(2031339142381550, "Sharpshooter", Dps, GUNSLINGER, 2031339142381598),
+	discipline!(2031339142381551, "Saboteur", Dps, GUNSLINGER, 2031339142381599),
+	discipline!(2031339142381552, "Dirty Fighting", Dps, GUNSLINGER, 2031339142381600),
+];
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	use crate::parser::{action::Action, line::Line};
+
+	#[test]
+	fn from_log_line() {
+		// a german client, only the ids can match
+		let l = Line::new("[20:00:00.000] [@Tank#1|(0,0,0,0)|(100/100)] [] [] [DisciplineChanged {836045448953665}: Juggernaut {16141170711935532310}/Unsterblichkeit {2031339142381578}]").unwrap();
+		let Action::DisciplineChanged { class, spec } = &l.action else {
+			panic!("{:?}", l.action);
+		};
+		let d = Discipline::find(spec).unwrap();
+		assert_eq!(d.name, "Immortal");
+		assert_eq!(d.role, Role::Tank);
+		assert_eq!(d.class().name, "Juggernaut");
+		assert_eq!(Class::find(class).unwrap().id, JUGGERNAUT);
+		assert!(Discipline::find(&NamedID::new("Unsterblichkeit {1}")).is_none());
+
+		// an english name is never overridden by an id that belongs to another discipline
+		let d = Discipline::find(&NamedID::new("Vengeance {2031339142381578}")).unwrap();
+		assert_eq!(d.name, "Vengeance");
+	}
+
+	#[test]
+	fn registry_is_consistent() {
+		for c in CLASSES.iter() {
+			assert_eq!(c.mirror().mirror().id, c.id, "{}", c.name);
+			assert_ne!(c.mirror().faction, c.faction, "{}", c.name);
+			assert_eq!(c.disciplines().count(), 3, "{}", c.name);
+		}
+
+		for d in DISCIPLINES.iter() {
+			let m = d.mirror();
+			assert_eq!(m.mirror().id, d.id, "{}", d.name);
+			assert_eq!(m.role, d.role, "{}", d.name);
+			assert_eq!(m.class().id, d.class().mirror, "{}", d.name);
+			assert_eq!(m.pair_id(), d.pair_id(), "{}", d.name);
+		}
+	}
+}
@@ -1,23 +1,163 @@
 use chrono::{Duration, NaiveTime};
 
+use std::collections::HashMap;
 use std::ops::Sub;
+use std::sync::Arc;
 
-use super::actor_stats::{ActorStats, Meter};
+use super::action::Difficulty;
+use super::actor_stats::{ActorStats, Meter, MeterRow};
+use super::bosses::{Boss, Bosses};
+use super::phase::{Phase, PhaseDef};
+use super::reader::CaughtUp;
+use super::role::Role;
 use super::sorted_vec::SortedVec;
 use super::utils::fmt_num;
 use super::*;
+use crate::metrics::Metrics;
+#[cfg(feature = "webhook")]
+use crate::webhook::Webhook;
 
-fn new_sorted_by_health() -> SortedVec<ActorStats> {
+pub(crate) fn new_sorted_by_health() -> SortedVec<ActorStats> {
 	SortedVec::<ActorStats>::new(|a, b| b.max_health.cmp(&a.max_health))
 }
 
-fn new_sorted_by_dmg() -> SortedVec<ActorStats> {
+pub(crate) fn new_sorted_by_dmg() -> SortedVec<ActorStats> {
 	SortedVec::<ActorStats>::new(|a, b| a.dmg_total.total.cmp(&b.dmg_total.total))
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Totals {
+	pub players: usize,
+	pub deaths: i32,
+
+	pub dmg_out: Meter,
+	pub dmg_in: Meter,
+	pub heal_out: Meter,
+	pub heal_in: Meter,
+}
+
+impl Totals {
+	fn add(&mut self, a: &ActorStats) {
+		self.players += 1;
+		self.deaths += a.deaths;
+		self.dmg_out += &a.all_dmg_out();
+		self.dmg_in += &a.all_dmg_in();
+		self.heal_out += &a.all_heal_out();
+		self.heal_in += &a.all_heal_in();
+	}
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Summary {
+	pub elapsed: i64,
+	pub raid: Totals,
+	pub roles: Vec<(Role, Totals)>,
+}
+
+impl Summary {
+	pub fn dps(&self) -> f64 {
+		self.raid.dmg_out.xps(self.elapsed)
+	}
+
+	pub fn hps(&self) -> f64 {
+		self.raid.heal_out.xps(self.elapsed)
+	}
+
+	pub fn dtps(&self) -> f64 {
+		self.raid.dmg_in.xps(self.elapsed)
+	}
+
+	pub fn role(&self, role: Role) -> Option<&Totals> {
+		self.roles.iter().find(|(r, _)| *r == role).map(|(_, t)| t)
+	}
+
+	pub fn role_dps(&self, role: Role) -> f64 {
+		self.role(role).map_or(0., |t| t.dmg_out.xps(self.elapsed))
+	}
+
+	pub fn role_hps(&self, role: Role) -> f64 {
+		self.role(role).map_or(0., |t| t.heal_out.xps(self.elapsed))
+	}
+}
+
+// the raw numbers behind a row of Encounter::get_vec_for
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayerRow {
+	pub id: NamedID,
+	pub spec: String,
+	pub role: Role,
+	pub meter: MeterRow,
+}
+
+impl PlayerRow {
+	// name (spec), # casts, total, crit %, apm, xps
+	pub fn cells(&self) -> Vec<String> {
+		vec![
+			if !self.spec.is_empty() {
+				format!("{} ({})", self.id.name, self.spec)
+			} else {
+				self.id.name.clone()
+			},
+			fmt_num(self.meter.casts as f64),
+			fmt_num(self.meter.total as f64),
+			fmt_num(self.meter.crit_pct) + "%",
+			fmt_num(self.meter.apm),
+			fmt_num(self.meter.xps),
+		]
+	}
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Outcome {
+	Kill,
+	Wipe,
+	Reset,
+	Trash,
+
+	#[default]
+	InProgress,
+}
+
+impl Outcome {
+	pub fn name(&self) -> &'static str {
+		match self {
+			Self::Kill => "kill",
+			Self::Wipe => "wipe",
+			Self::Reset => "reset",
+			Self::Trash => "trash",
+			Self::InProgress => "in progress",
+		}
+	}
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct EncounterOptions {
+	// roll companion damage and healing into the owner's stats
+	pub merge_companions: bool,
+	// end an encounter that had no lines for this many seconds of log time, for the times
+	// the game doesn't log ExitCombat
+	pub idle_timeout: Option<i64>,
+	// trash encounters are neither reported nor kept
+	pub bosses_only: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Encounter {
 	pub area: String,
+	pub area_id: u64,
+	pub difficulty: Difficulty,
+	// per boss pull counter within the session, 0 for trash
+	pub pull: u32,
+	// the player who wrote the log
+	pub local_player: Option<NamedID>,
+	pub options: EncounterOptions,
 	pub start: NaiveTime,
 	pub ts: NaiveTime,
 	pub end: NaiveTime,
@@ -25,6 +165,16 @@ pub struct Encounter {
 	//pub lines: Vec<Line>,
 	pub players: SortedVec<ActorStats>,
 	pub npcs: SortedVec<ActorStats>,
+
+	#[cfg_attr(feature = "serde", serde(skip))]
+	pub phase_defs: Arc<Vec<PhaseDef>>,
+	pub phases: Vec<Phase>,
+
+	#[cfg_attr(feature = "serde", serde(skip))]
+	checked_npcs: usize,
+	// the class and discipline of every player, the game logs them when zoning in
+	#[cfg_attr(feature = "serde", serde(skip))]
+	disciplines: HashMap<NamedID, (NamedID, NamedID)>,
 }
 
 impl Encounter {
@@ -46,7 +196,7 @@ impl Encounter {
 			}
 
 			Action::ExitCombat => {
-				self.end = l.ts;
+				self.close(l.ts);
 				return false;
 			}
 
@@ -58,141 +208,264 @@ impl Encounter {
 			}
 		};
 
-		if let Some(ref src) = l.source {
-			let id = src.get_id();
-			let v = if src.is_npc() {
-				&mut self.npcs
-			} else {
-				&mut self.players
-			};
+		let spawned = [&l.source, &l.target]
+			.into_iter()
+			.flatten()
+			.filter(|a| a.is_npc() && !self.npcs.iter().any(|n| n.id == a.id))
+			.map(|a| a.id.id)
+			.collect::<Vec<_>>();
+		update_actors(
+			&mut self.players,
+			&mut self.npcs,
+			l,
+			&self.options,
+			&self.disciplines,
+		);
+		self.check_phases(l, &spawned);
+		if let Some(p) = self.phases.last_mut() {
+			p.append(l, &self.options, &self.disciplines);
+		}
+
+		true
+	}
 
-			v.update(
-				|| ActorStats::new(id.clone()),
-				|a| a.id == id,
-				|a| a.update(&l.source, &l.target, &l.action),
-			)
+	fn close(&mut self, ts: NaiveTime) {
+		self.end = ts;
+		if let Some(p) = self.phases.last_mut() {
+			p.end = ts;
 		}
+	}
 
-		if let Some(ref dst) = l.target {
-			let id = dst.get_id();
-			let v = if dst.is_npc() {
-				&mut self.npcs
-			} else {
-				&mut self.players
-			};
+	// the time of the last line, EnterCombat included
+	fn last_ts(&self) -> NaiveTime {
+		self.ts.max(self.start)
+	}
 
-			v.update(
-				|| ActorStats::new(id.clone()),
-				|a| a.id == id,
-				|a| a.update(&l.source, &l.target, &l.action),
-			)
+	fn check_phases(&mut self, l: &Line, spawned: &[u64]) {
+		if self.phase_defs.is_empty() {
+			return;
+		}
+		if self.phases.is_empty() {
+			self.phases.push(Phase::new("Start", None, self.start));
 		}
 
-		true
+		let defs = self.phase_defs.clone();
+		for (i, def) in defs.iter().enumerate() {
+			// a def doesn't restart the phase it started, one that can only happen once
+			// doesn't come back after another phase either
+			let started = |p: &Phase| p.def == Some(i);
+			if self.phases.last().is_some_and(started)
+				|| (def.trigger.once() && self.phases.iter().any(started))
+			{
+				continue;
+			}
+			if def.boss.is_some() && def.boss != self.boss_name() {
+				continue;
+			}
+			if def.trigger.matches(l, spawned) {
+				if let Some(p) = self.phases.last_mut() {
+					p.end = l.ts;
+				}
+				self.phases.push(Phase::new(&def.name, Some(i), l.ts));
+			}
+		}
+	}
+
+	pub fn phase(&self, name: &str) -> Option<&Phase> {
+		self.phases.iter().find(|p| p.name == name)
 	}
 
-	pub fn get_vec_for<F: Fn(&ActorStats) -> Meter>(
-		m: &SortedVec<ActorStats>,
+	pub fn current_phase(&self) -> Option<&Phase> {
+		self.phases.last()
+	}
+
+	pub fn rows_for<'a, I: Iterator<Item = &'a ActorStats>, F: Fn(&ActorStats) -> Meter>(
+		m: I,
 		elapsed: i64,
 		fn_: F,
-	) -> Vec<(Vec<String>, f64)> {
-		let mut hm = m
-			.iter()
-			.map(|v| {
-				let m = fn_(v);
-				let xps = m.xps(elapsed);
-				let apm = m.apm(elapsed);
-				let o = vec![
-					if v.spec.id != 0 {
-						format!("{} ({})", v.id.name, v.spec.name)
-					} else {
-						v.id.name.clone()
-					},
-					fmt_num(m.casts as f64),
-					fmt_num(m.total as f64),
-					fmt_num((m.crits as f64 / m.casts as f64) * 100.) + "%",
-					fmt_num(apm),
-					fmt_num(xps),
-				];
-				(o, xps)
+	) -> Vec<PlayerRow> {
+		let mut rows = m
+			.map(|v| PlayerRow {
+				id: v.id.clone(),
+				spec: if v.spec.id != 0 {
+					v.spec_name()
+				} else {
+					String::new()
+				},
+				role: v.role(),
+				meter: fn_(v).to_row(elapsed),
 			})
 			.collect::<Vec<_>>();
 
-		hm.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+		rows.sort_by(|a, b| b.meter.xps.total_cmp(&a.meter.xps));
 
-		hm
+		rows
+	}
+
+	pub fn get_vec_for<'a, I: Iterator<Item = &'a ActorStats>, F: Fn(&ActorStats) -> Meter>(
+		m: I,
+		elapsed: i64,
+		fn_: F,
+	) -> Vec<(Vec<String>, f64)> {
+		Self::rows_for(m, elapsed, fn_)
+			.into_iter()
+			.map(|r| (r.cells(), r.meter.xps))
+			.collect()
+	}
+
+	pub fn heals_out_rows(&self) -> Vec<PlayerRow> {
+		let elapsed = self.elapsed().num_seconds().max(1);
+		Self::rows_for(self.players.iter(), elapsed, ActorStats::all_heal_out)
+	}
+
+	pub fn dmg_out_rows(&self) -> Vec<PlayerRow> {
+		let elapsed = self.elapsed().num_seconds().max(1);
+		Self::rows_for(self.players.iter(), elapsed, ActorStats::all_dmg_out)
+	}
+
+	pub fn heals_in_rows(&self) -> Vec<PlayerRow> {
+		let elapsed = self.elapsed().num_seconds().max(1);
+		Self::rows_for(self.players.iter(), elapsed, ActorStats::all_heal_in)
+	}
+
+	pub fn dmg_in_rows(&self) -> Vec<PlayerRow> {
+		let elapsed = self.elapsed().num_seconds().max(1);
+		Self::rows_for(self.players.iter(), elapsed, ActorStats::all_dmg_in)
+	}
+
+	pub fn dmg_out_rows_by_role(&self, role: Role) -> Vec<PlayerRow> {
+		let elapsed = self.elapsed().num_seconds().max(1);
+		Self::rows_for(self.players_by_role(role), elapsed, ActorStats::all_dmg_out)
 	}
 
 	pub fn heals_out(&self) -> Vec<(Vec<String>, f64)> {
 		let elapsed = self.elapsed().num_seconds();
-		Self::get_vec_for(&self.players, elapsed, ActorStats::all_heal_out)
+		Self::get_vec_for(self.players.iter(), elapsed, ActorStats::all_heal_out)
 	}
 
 	pub fn dmg_out(&self) -> Vec<(Vec<String>, f64)> {
 		let elapsed = self.elapsed().num_seconds();
-		Self::get_vec_for(&self.players, elapsed, ActorStats::all_dmg_out)
+		Self::get_vec_for(self.players.iter(), elapsed, ActorStats::all_dmg_out)
 	}
 
 	pub fn spells_out(&self) -> Vec<(Vec<String>, f64)> {
 		let elapsed = self.elapsed().num_seconds();
-		Self::get_vec_for(&self.players, elapsed, ActorStats::all_spells_out)
+		Self::get_vec_for(self.players.iter(), elapsed, ActorStats::all_spells_out)
 	}
 
 	pub fn heals_in(&self) -> Vec<(Vec<String>, f64)> {
 		let elapsed = self.elapsed().num_seconds();
-		Self::get_vec_for(&self.players, elapsed, ActorStats::all_heal_in)
+		Self::get_vec_for(self.players.iter(), elapsed, ActorStats::all_heal_in)
 	}
 
 	pub fn dmg_in(&self) -> Vec<(Vec<String>, f64)> {
 		let elapsed = self.elapsed().num_seconds();
-		Self::get_vec_for(&self.players, elapsed, ActorStats::all_dmg_in)
+		Self::get_vec_for(self.players.iter(), elapsed, ActorStats::all_dmg_in)
 	}
 
-	pub fn npc_by_health(&self, filter_bosses: bool) -> Vec<(String, i32)> {
-		let it = self.npcs.iter();
-		if filter_bosses {
-			let players_health = self.players.iter().fold(0, |v, a| v + a.max_health);
-			let is_boss = self
-				.npcs
-				.v
-				.first()
-				.map_or_else(|| false, |v| v.max_health > players_health);
-
-			if is_boss {
-				it.filter(|n| n.max_health > players_health / 2)
-					.map(|v| {
-						let n = v.id.name.clone();
-						let h = v.max_health;
-						(n, h)
-					})
-					.collect::<Vec<_>>()
+	pub fn players_by_role(&self, role: Role) -> impl Iterator<Item = &ActorStats> {
+		self.players.iter().filter(move |p| p.role() == role)
+	}
+
+	pub fn heals_out_by_role(&self, role: Role) -> Vec<(Vec<String>, f64)> {
+		let elapsed = self.elapsed().num_seconds();
+		Self::get_vec_for(
+			self.players_by_role(role),
+			elapsed,
+			ActorStats::all_heal_out,
+		)
+	}
+
+	pub fn dmg_out_by_role(&self, role: Role) -> Vec<(Vec<String>, f64)> {
+		let elapsed = self.elapsed().num_seconds();
+		Self::get_vec_for(self.players_by_role(role), elapsed, ActorStats::all_dmg_out)
+	}
+
+	pub fn dmg_in_by_role(&self, role: Role) -> Vec<(Vec<String>, f64)> {
+		let elapsed = self.elapsed().num_seconds();
+		Self::get_vec_for(self.players_by_role(role), elapsed, ActorStats::all_dmg_in)
+	}
+
+	pub fn summary(&self) -> Summary {
+		let mut sum = Summary {
+			// a zero length encounter would divide by zero
+			elapsed: self.elapsed().num_seconds().max(1),
+			..Summary::default()
+		};
+
+		for p in self.players.iter() {
+			sum.raid.add(p);
+
+			let role = p.role();
+			if let Some((_, t)) = sum.roles.iter_mut().find(|(r, _)| *r == role) {
+				t.add(p);
 			} else {
-				it.map(|v| {
-					let n = v.id.name.clone();
-					let h = v.max_health;
-					(n, h)
-				})
-				.collect::<Vec<_>>()
+				let mut t = Totals::default();
+				t.add(p);
+				sum.roles.push((role, t));
 			}
-		} else {
-			it.map(|v| {
-				let n = v.id.name.clone();
-				let h = v.max_health;
-				(n, h)
-			})
-			.collect::<Vec<_>>()
 		}
+		sum.roles.sort_by_key(|(r, _)| *r);
+
+		sum
 	}
 
-	pub fn is_boss(&self) -> bool {
+	// with filter_bosses only the boss npcs are listed in a boss fight
+	pub fn npc_by_health(&self, filter_bosses: bool) -> Vec<(String, i32)> {
+		let boss = self.boss().filter(|_| filter_bosses);
+		self.npcs
+			.iter()
+			.filter(|n| boss.is_none_or(|b| b.has_npc(&n.id)))
+			.map(|v| (v.id.name.clone(), v.max_health))
+			.collect()
+	}
+
+	pub fn boss(&self) -> Option<&'static Boss> {
+		let area = NamedID {
+			id: self.area_id,
+			name: self.area.clone(),
+		};
+		Bosses::builtin().find(&area, self.npcs.iter().map(|n| &n.id))
+	}
+
+	// for fights the database doesn't know, or doesn't recognise on a non-english client: the
+	// npc with the most health if it has more than the whole group together
+	fn unlisted_boss(&self) -> Option<&ActorStats> {
+		if self.boss().is_some() {
+			return None;
+		}
 		let players_health = self.players.iter().fold(0, |v, a| v + a.max_health);
-		if let Some(npc) = self.npcs.v.first() {
-			npc.max_health > players_health
-		} else {
-			false
+		self.npcs
+			.iter()
+			.next()
+			.filter(|n| n.max_health > players_health)
+	}
+
+	pub fn is_boss(&self) -> bool {
+		self.boss().is_some() || self.unlisted_boss().is_some()
+	}
+
+	// the database's name for the fight, the npc's name for a boss it doesn't know
+	pub fn boss_name(&self) -> Option<String> {
+		match self.boss() {
+			Some(b) => Some(b.name.clone()),
+			None => self.unlisted_boss().map(|n| n.id.name.clone()),
+		}
+	}
+
+	// "Brontes (8m Veteran)" or "Trash"
+	pub fn name(&self) -> String {
+		match self.boss_name() {
+			Some(b) if self.difficulty.is_known() => format!("{b} ({})", self.difficulty),
+			Some(b) => b,
+			None => "Trash".to_owned(),
 		}
 	}
 
+	pub fn player(&self, id: &NamedID) -> Option<&ActorStats> {
+		self.players.iter().find(|p| &p.id == id)
+	}
+
 	pub fn player_by_name(&self, name: &str) -> Option<&ActorStats> {
 		let name = if let Some(idx) = name.find('(') {
 			name[..idx - 1].to_owned()
@@ -206,8 +479,71 @@ impl Encounter {
 		}
 	}
 
+	pub fn boss_npcs(&self) -> impl Iterator<Item = &ActorStats> {
+		let boss = self.boss();
+		let unlisted = self.unlisted_boss().map(|n| n.id.clone());
+		self.npcs.iter().filter(move |n| match boss {
+			Some(b) => b.has_npc(&n.id),
+			None => unlisted.as_ref() == Some(&n.id),
+		})
+	}
+
+	pub fn me(&self) -> Option<&ActorStats> {
+		self.player(self.local_player.as_ref()?)
+	}
+
+	pub fn is_me(&self, p: &ActorStats) -> bool {
+		self.local_player.as_ref() == Some(&p.id)
+	}
+
 	pub fn is_boss_dead(&self) -> bool {
-		self.npcs.iter().all(|v| v.is_dead())
+		self.is_boss() && self.boss_npcs().all(|v| v.is_dead())
+	}
+
+	pub fn is_wipe(&self) -> bool {
+		let mut players = self.players.iter().filter(|p| p.is_player()).peekable();
+		players.peek().is_some() && players.all(|p| p.is_dead())
+	}
+
+	// combined health of all the boss npcs
+	pub fn boss_health_pct(&self) -> f64 {
+		let (health, max) = self.boss_npcs().fold((0, 0), |(h, m), n| {
+			(h + n.health as i64, m + n.max_health as i64)
+		});
+		if max == 0 {
+			return 0.;
+		}
+		100. * health as f64 / max as f64
+	}
+
+	pub fn outcome(&self) -> Outcome {
+		if !self.is_boss() {
+			Outcome::Trash
+		} else if self.is_boss_dead() {
+			Outcome::Kill
+		} else if self.is_wipe() {
+			Outcome::Wipe
+		} else if self.end == NaiveTime::MIN {
+			Outcome::InProgress
+		} else {
+			Outcome::Reset
+		}
+	}
+
+	// "Brontes (8m Veteran) pull 7, wipe at 23%"
+	pub fn status(&self) -> String {
+		let outcome = self.outcome();
+		match outcome {
+			Outcome::Trash => self.name(),
+			Outcome::Kill => format!("{} pull {}, kill", self.name(), self.pull),
+			_ => format!(
+				"{} pull {}, {} at {:.0}%",
+				self.name(),
+				self.pull,
+				outcome.name(),
+				self.boss_health_pct()
+			),
+		}
 	}
 
 	pub fn elapsed(&self) -> Duration {
@@ -219,11 +555,69 @@ impl Encounter {
 	}
 }
 
+// companions are their owner when merging companions
+pub(crate) fn actor_id(a: &Actor, opts: &EncounterOptions) -> NamedID {
+	if opts.merge_companions && a.is_companion() {
+		a.id.clone()
+	} else {
+		a.get_id()
+	}
+}
+
+pub(crate) fn update_actors(
+	players: &mut SortedVec<ActorStats>,
+	npcs: &mut SortedVec<ActorStats>,
+	l: &Line,
+	opts: &EncounterOptions,
+	disciplines: &HashMap<NamedID, (NamedID, NamedID)>,
+) {
+	let mut seen = None;
+	for a in [&l.source, &l.target].into_iter().flatten() {
+		let id = actor_id(a, opts);
+		// when merging, a companion healing its owner is one update, not two
+		if opts.merge_companions && seen.as_ref() == Some(&id) {
+			continue;
+		}
+		let v = if a.is_npc() {
+			&mut *npcs
+		} else {
+			&mut *players
+		};
+
+		v.update(
+			|| {
+				let mut s = ActorStats::new(id.clone());
+				s.merge_companions = opts.merge_companions;
+				if let Some((class, spec)) = disciplines.get(&id) {
+					s.class = class.clone();
+					s.spec = spec.clone();
+				}
+				s
+			},
+			|a| a.id == id,
+			|a| a.update(l.ts, &l.source, &l.target, &l.action),
+		);
+		seen = Some(id);
+	}
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct Encounters {
 	all: Vec<Encounter>,
 	curr: Option<Encounter>,
-	last_area: String,
+	last_area: NamedID,
+	last_difficulty: Difficulty,
+	local_player: Option<NamedID>,
+	// every player's last DisciplineChanged, new encounters start with them
+	disciplines: HashMap<NamedID, (NamedID, NamedID)>,
+	pulls: HashMap<String, u32>,
+	phase_defs: Arc<Vec<PhaseDef>>,
+	options: EncounterOptions,
+	#[cfg(feature = "webhook")]
+	webhook: Option<Webhook>,
+	// set when following a log, lines counts what was pushed so far
+	caught_up: Option<CaughtUp>,
+	lines: usize,
 }
 
 impl Encounters {
@@ -231,34 +625,103 @@ impl Encounters {
 		Self::default()
 	}
 
+	pub fn local_player(&self) -> Option<&NamedID> {
+		self.local_player.as_ref()
+	}
+
+	pub fn set_options(&mut self, opts: EncounterOptions) {
+		self.options = opts;
+	}
+
+	pub fn set_phases(&mut self, defs: Vec<PhaseDef>) {
+		self.phase_defs = Arc::new(defs);
+	}
+
+	// posts a summary of every boss kill / wipe when combat ends, once the log is followed live
+	#[cfg(feature = "webhook")]
+	pub fn set_webhook(&mut self, hook: Option<Webhook>) {
+		self.webhook = hook;
+	}
+
+	pub fn set_caught_up(&mut self, c: CaughtUp) {
+		self.caught_up = Some(c);
+	}
+
+	// the last line was written while the log was followed, not read back from earlier
+	pub fn is_live(&self) -> bool {
+		self.caught_up
+			.as_ref()
+			.is_some_and(|c| c.is_live(self.lines))
+	}
+
 	pub async fn process<F: Fn(&Encounter, &Line)>(&mut self, rx: &mut Receiver<Line>, process: F) {
+		let m = Metrics::global();
 		while let Some(l) = rx.recv().await {
-			match l.action {
-				Action::AreaEntered(n) => self.last_area = n.name,
+			if matches!(l.action, Action::EnterCombat) {
+				m.encounter_started();
+			}
+			self.push(l, &|e, l| {
+				m.update_players(e, matches!(l.action, Action::ExitCombat));
+				process(e, l)
+			});
+		}
+	}
 
-				Action::EnterCombat => {
-					let mut e = Encounter::new(self.last_area.clone());
-					e.append(&l);
-					if let Some(oe) = self.curr.replace(e) {
-						self.all.push(oe);
-					}
+	pub fn push<F: Fn(&Encounter, &Line)>(&mut self, l: Line, process: &F) {
+		self.lines += 1;
+		self.detect_local_player(&l);
+		self.keep_discipline(&l);
+		if let Some(t) = self.options.idle_timeout {
+			let idle = self
+				.curr
+				.as_ref()
+				.is_some_and(|e| l.ts - e.last_ts() > Duration::seconds(t));
+			if idle {
+				if let Some(mut e) = self.curr.take() {
+					e.close(e.last_ts());
+					self.end(e, &l, process);
 				}
+			}
+		}
+		match l.action {
+			Action::AreaEntered { area, difficulty } => {
+				self.last_area = area;
+				self.last_difficulty = difficulty;
+			}
 
-				Action::ExitCombat => {
-					if let Some(e) = &mut self.curr.take() {
-						e.append(&l);
-						process(e, &l);
-						self.all.push(e.clone());
-					}
+			Action::EnterCombat => {
+				// the previous fight's ExitCombat wasn't logged
+				if let Some(oe) = self.curr.take() {
+					self.end(oe, &l, process);
 				}
+				let mut e = Encounter::new(self.last_area.name.clone());
+				e.area_id = self.last_area.id;
+				e.difficulty = self.last_difficulty;
+				e.phase_defs = self.phase_defs.clone();
+				e.local_player = self.local_player.clone();
+				e.disciplines = self.disciplines.clone();
+				e.options = self.options.clone();
+				e.append(&l);
+				self.curr = Some(e);
+			}
 
-				_ => {
-					if let Some(e) = &mut self.curr {
-						if e.end != NaiveTime::MIN {
-							continue;
-						}
+			Action::ExitCombat => {
+				if let Some(mut e) = self.curr.take() {
+					Self::number_pull(&mut self.pulls, &mut e);
+					e.append(&l);
+					self.end(e, &l, process);
+				}
+			}
+
+			_ => {
+				if let Some(e) = &mut self.curr {
+					if e.end != NaiveTime::MIN {
+						return;
+					}
 
-						if e.append(&l) {
+					if e.append(&l) {
+						Self::number_pull(&mut self.pulls, e);
+						if !self.options.bosses_only || e.pull != 0 {
 							process(e, &l);
 						}
 					}
@@ -266,4 +729,351 @@ impl Encounters {
 			}
 		}
 	}
+
+	fn end<F: Fn(&Encounter, &Line)>(&mut self, e: Encounter, l: &Line, process: &F) {
+		if !self.wanted(&e) {
+			return;
+		}
+		process(&e, l);
+		#[cfg(feature = "webhook")]
+		if let Some(hook) = self.webhook.as_ref().filter(|_| self.is_live()) {
+			hook.send(&e);
+		}
+		self.all.push(e);
+	}
+
+	// pulls are only numbered for bosses
+	fn wanted(&self, e: &Encounter) -> bool {
+		!self.options.bosses_only || e.pull != 0
+	}
+
+	pub fn all(&self) -> &[Encounter] {
+		&self.all
+	}
+
+	pub fn current(&self) -> Option<&Encounter> {
+		self.curr.as_ref()
+	}
+
+	// the encounters that ended, without the one still in progress
+	pub fn finished(self) -> Vec<Encounter> {
+		self.all
+	}
+
+	// all encounters, including the one still in progress
+	pub fn finish(mut self) -> Vec<Encounter> {
+		if let Some(e) = self.curr.take().filter(|e| self.wanted(e)) {
+			self.all.push(e);
+		}
+		self.all
+	}
+
+	// AreaEntered is only ever logged for the owner of the log, DisciplineChanged is logged
+	// for group members as well so it's only used until the first AreaEntered
+	fn detect_local_player(&mut self, l: &Line) {
+		let Some(src) = l.source.as_ref().filter(|s| s.is_player()) else {
+			return;
+		};
+		match l.action {
+			Action::AreaEntered { .. } => self.local_player = Some(src.id.clone()),
+			Action::DisciplineChanged { .. } if self.local_player.is_none() => {
+				self.local_player = Some(src.id.clone())
+			}
+			_ => return,
+		}
+		if let Some(e) = &mut self.curr {
+			e.local_player = self.local_player.clone();
+		}
+	}
+
+	fn keep_discipline(&mut self, l: &Line) {
+		let (Action::DisciplineChanged { class, spec }, Some(src)) = (&l.action, &l.source) else {
+			return;
+		};
+		if !src.is_player() {
+			return;
+		}
+		let d = (class.clone(), spec.clone());
+		if let Some(e) = &mut self.curr {
+			e.disciplines.insert(src.id.clone(), d.clone());
+		}
+		self.disciplines.insert(src.id.clone(), d);
+	}
+
+	// only looks the boss up again when a new npc joined the fight
+	fn number_pull(pulls: &mut HashMap<String, u32>, e: &mut Encounter) {
+		if e.pull != 0 || e.checked_npcs == e.npcs.len() {
+			return;
+		}
+		e.checked_npcs = e.npcs.len();
+		if e.is_boss() {
+			let n = pulls.entry(e.name()).or_default();
+			*n += 1;
+			e.pull = *n;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	use crate::fixtures::BRONTES;
+	use tokio::sync::mpsc::channel;
+
+	async fn feed(log: &str, phases: Vec<PhaseDef>) -> Encounters {
+		feed_with(log, phases, EncounterOptions::default()).await
+	}
+
+	async fn feed_with(log: &str, phases: Vec<PhaseDef>, opts: EncounterOptions) -> Encounters {
+		let (tx, mut rx) = channel(64);
+		for l in log.lines() {
+			tx.send(Line::new(l).unwrap()).await.unwrap();
+		}
+		drop(tx);
+
+		let mut encs = Encounters::new();
+		encs.set_phases(phases);
+		encs.set_options(opts);
+		encs.process(&mut rx, |_, _| {}).await;
+		encs
+	}
+
+	#[tokio::test]
+	async fn kill_and_wipe() {
+		let encs = feed(BRONTES, vec![]).await;
+		assert_eq!(encs.all.len(), 2);
+
+		let wipe = &encs.all[0];
+		assert_eq!(wipe.name(), "Brontes (8m Veteran)");
+		assert_eq!(wipe.outcome(), Outcome::Wipe);
+		assert_eq!(wipe.status(), "Brontes (8m Veteran) pull 1, wipe at 77%");
+
+		let kill = &encs.all[1];
+		assert_eq!(kill.outcome(), Outcome::Kill);
+		assert_eq!(kill.pull, 2);
+
+		assert_eq!(encs.local_player().unwrap().name, "Tank");
+		assert_eq!(kill.me().unwrap().all_dmg_out().total, 1000);
+	}
+
+	#[tokio::test]
+	async fn unlisted_boss() {
+		// a german client, the database only knows the english names
+		let log = BRONTES
+			.replace("The Dread Fortress", "Die Schreckensfestung")
+			.replace("Dread Master Brontes", "Schreckensmeisterin Brontes");
+		let encs = feed(&log, vec![]).await;
+		let (wipe, kill) = (&encs.all[0], &encs.all[1]);
+		assert!(wipe.boss().is_none());
+		assert_eq!(wipe.name(), "Schreckensmeisterin Brontes (8m Veteran)");
+		assert_eq!(wipe.outcome(), Outcome::Wipe);
+		assert_eq!(kill.outcome(), Outcome::Kill);
+		assert_eq!(kill.pull, 2);
+	}
+
+	#[tokio::test]
+	async fn discipline_from_zone_in() {
+		// logged after AreaEntered, outside of any fight
+		let log = BRONTES.replacen(
+			"\n",
+			"\n[20:00:00.500] [@Tank#1|(0,0,0,0)|(100/100)] [] [] [DisciplineChanged {836045448953665}: Juggernaut {16141170711935532310}/Immortal {2031339142381578}]\n",
+			1,
+		);
+		let encs = feed(&log, vec![]).await;
+		for e in encs.all.iter() {
+			assert_eq!(e.me().unwrap().spec_name(), "Immortal");
+			assert_eq!(e.me().unwrap().role(), Role::Tank);
+		}
+	}
+
+	#[test]
+	fn zero_length_summary() {
+		let mut encs = Encounters::new();
+		for l in BRONTES.lines().skip(6) {
+			let l = l.replace("[20:01:01.000]", "[20:01:00.000]");
+			let l = l.replace("[20:01:02.000]", "[20:01:00.000]");
+			encs.push(Line::new(&l).unwrap(), &|_, _| {});
+		}
+		let sum = encs.all[0].summary();
+		assert_eq!(sum.elapsed, 1);
+		assert_eq!(sum.dps(), 1000.);
+		assert!(sum.hps().is_finite() && sum.dtps().is_finite());
+	}
+
+	#[tokio::test]
+	async fn phases() {
+		use crate::parser::phase::Trigger;
+
+		let burn = PhaseDef::new(
+			"Burn",
+			Trigger::HealthBelow {
+				npc: 3266825467691008,
+				pct: 80.,
+			},
+		)
+		.for_boss("Brontes");
+		let smash = PhaseDef::new(
+			"Smash",
+			Trigger::AbilityActivated {
+				ability: 2,
+				npc: None,
+			},
+		);
+		let encs = feed(BRONTES, vec![burn.clone()]).await;
+
+		let wipe = &encs.all[0];
+		assert_eq!(wipe.phases.len(), 2);
+		let p = wipe.phase("Burn").unwrap();
+		assert_eq!(p.elapsed().num_seconds(), 2);
+		let tank = p.player(wipe.local_player.as_ref().unwrap()).unwrap();
+		assert_eq!(tank.all_dmg_out().total, 230);
+		assert_eq!(tank.all_dmg_in().total, 100);
+		// the whole breakdown, not just the totals
+		assert_eq!(p.spells_out()[0].0[0], "Tank");
+		assert!(tank.spells_out.iter().any(|m| m.id.name == "Strike"));
+		assert_eq!(
+			p.npcs.iter().next().unwrap().id.name,
+			"Dread Master Brontes"
+		);
+		assert!(wipe.phase("Start").unwrap().players.is_empty());
+
+		// abilities start their phase again after another phase, health only once
+		let kinetic = PhaseDef::new(
+			"Kinetic",
+			Trigger::AbilityActivated {
+				ability: 3,
+				npc: None,
+			},
+		);
+		let log = "\
+[20:00:01.000] [@Tank#1|(0,0,0,0)|(100/100)] [] [] [Event {836045448945472}: EnterCombat {836045448945489}]
+[20:00:02.000] [Dread Master Brontes {3266825467691008}:1|(0,0,0,0)|(1000/1000)] [] [Smash {2}] [Event {836045448945472}: AbilityActivate {836045448945479}]
+[20:00:03.000] [Dread Master Brontes {3266825467691008}:1|(0,0,0,0)|(1000/1000)] [] [Kinetic {3}] [Event {836045448945472}: AbilityActivate {836045448945479}]
+[20:00:04.000] [Dread Master Brontes {3266825467691008}:1|(0,0,0,0)|(1000/1000)] [] [Smash {2}] [Event {836045448945472}: AbilityActivate {836045448945479}]
+[20:00:05.000] [@Tank#1|(0,0,0,0)|(100/100)] [Dread Master Brontes {3266825467691008}:1|(0,0,0,0)|(500/1000)] [Strike {1}] [ApplyEffect {836045448945477}: Damage {836045448945501}] (500 kinetic {836045448940873})
+[20:00:06.000] [Dread Master Brontes {3266825467691008}:1|(0,0,0,0)|(500/1000)] [] [Smash {2}] [Event {836045448945472}: AbilityActivate {836045448945479}]
+[20:00:07.000] [@Tank#1|(0,0,0,0)|(100/100)] [Dread Master Brontes {3266825467691008}:1|(0,0,0,0)|(400/1000)] [Strike {1}] [ApplyEffect {836045448945477}: Damage {836045448945501}] (100 kinetic {836045448940873})
+[20:00:08.000] [Dread Master Brontes {3266825467691008}:1|(0,0,0,0)|(400/1000)] [] [Smash {2}] [Event {836045448945472}: AbilityActivate {836045448945479}]
+[20:00:09.000] [@Tank#1|(0,0,0,0)|(100/100)] [] [] [Event {836045448945472}: ExitCombat {836045448945490}]
+";
+		let encs = feed(log, vec![smash, kinetic, burn]).await;
+		let names = encs.all[0]
+			.phases
+			.iter()
+			.map(|p| p.name.as_str())
+			.collect::<Vec<_>>();
+		assert_eq!(
+			names,
+			vec!["Start", "Smash", "Kinetic", "Smash", "Burn", "Smash"]
+		);
+	}
+
+	#[tokio::test]
+	async fn companions() {
+		let log = "\
+[20:00:01.000] [@Tank#1|(0,0,0,0)|(100/100)] [] [] [Event {836045448945472}: EnterCombat {836045448945489}]
+[20:00:02.000] [@Tank#1|(0,0,0,0)|(100/100)] [Training Droid {5}:1|(0,0,0,0)|(800/1000)] [Strike {1}] [ApplyEffect {836045448945477}: Damage {836045448945501}] (200 kinetic {836045448940873})
+[20:00:03.000] [@Tank#1/Khem Val {3}:7|(0,0,0,0)|(50/50)] [Training Droid {5}:1|(0,0,0,0)|(700/1000)] [Strike {1}] [ApplyEffect {836045448945477}: Damage {836045448945501}] (100 kinetic {836045448940873})
+[20:00:03.000] [@Tank#1/Khem Val {3}:7|(0,0,0,0)|(50/50)] [@Tank#1|(0,0,0,0)|(100/100)] [Mend {4}] [ApplyEffect {836045448945477}: Heal {836045448945500}] (10)
+[20:00:04.000] [@Tank#1|(0,0,0,0)|(100/100)] [] [] [Event {836045448945472}: ExitCombat {836045448945490}]
+";
+		let split = feed(log, vec![]).await;
+		let e = &split.all[0];
+		assert_eq!(e.players.len(), 2);
+		let tank = NamedID {
+			id: 1,
+			name: "Tank".to_owned(),
+		};
+		assert_eq!(e.player(&tank).unwrap().all_dmg_out().total, 200);
+		let khem = e.players.iter().find(|p| p.id.name == "Khem Val (Tank)");
+		assert_eq!(khem.unwrap().all_dmg_out().total, 100);
+
+		let opts = EncounterOptions {
+			merge_companions: true,
+			..Default::default()
+		};
+		let merged = feed_with(log, vec![], opts).await;
+		let e = &merged.all[0];
+		assert_eq!(e.players.len(), 1);
+		let tank = e.player(&tank).unwrap();
+		assert_eq!(tank.all_dmg_out().total, 300);
+		assert_eq!(tank.all_heal_out().total, 10);
+		assert!(tank
+			.spells_out
+			.iter()
+			.any(|m| m.id.name == "Strike (Khem Val)"));
+	}
+
+	#[tokio::test]
+	async fn idle_timeout() {
+		// the wipe's ExitCombat never made it into the log
+		let log = BRONTES
+			.lines()
+			.filter(|l| !l.starts_with("[20:00:04.000]"))
+			.collect::<Vec<_>>()
+			.join("\n");
+		let encs = feed(&log, vec![]).await;
+		assert_eq!(encs.all.len(), 2);
+		assert_eq!(encs.all[0].end, NaiveTime::MIN);
+
+		let opts = EncounterOptions {
+			idle_timeout: Some(30),
+			..Default::default()
+		};
+		let encs = feed_with(&log, vec![], opts).await;
+		assert_eq!(encs.all.len(), 2);
+		let wipe = &encs.all[0];
+		assert_eq!(wipe.end.to_string(), "20:00:03");
+		assert_eq!(wipe.outcome(), Outcome::Wipe);
+		assert_eq!(encs.all[1].outcome(), Outcome::Kill);
+	}
+
+	#[test]
+	fn replaced_by_next_pull() {
+		let mut encs = Encounters::new();
+		let ended = std::cell::RefCell::new(vec![]);
+		let process = |e: &Encounter, l: &Line| {
+			if matches!(l.action, Action::EnterCombat) {
+				ended.borrow_mut().push(e.start.to_string());
+			}
+		};
+		for l in BRONTES.lines().filter(|l| !l.starts_with("[20:00:04.000]")) {
+			encs.push(Line::new(l).unwrap(), &process);
+		}
+		assert_eq!(*ended.borrow(), ["20:00:01"]);
+		assert_eq!(encs.all.len(), 2);
+		assert_eq!(encs.all[0].outcome(), Outcome::Wipe);
+	}
+
+	#[tokio::test]
+	async fn bosses_only() {
+		let log = format!(
+			"{BRONTES}\
+[20:02:00.000] [@Tank#1|(0,0,0,0)|(100/100)] [] [] [Event {{836045448945472}}: EnterCombat {{836045448945489}}]
+[20:02:01.000] [@Tank#1|(0,0,0,0)|(100/100)] [Training Droid {{5}}:1|(0,0,0,0)|(0/80)] [Strike {{1}}] [ApplyEffect {{836045448945477}}: Damage {{836045448945501}}] (200 kinetic {{836045448940873}})
+[20:02:02.000] [@Tank#1|(0,0,0,0)|(100/100)] [] [] [Event {{836045448945472}}: ExitCombat {{836045448945490}}]
+"
+		);
+		assert_eq!(feed(&log, vec![]).await.all.len(), 3);
+
+		let opts = EncounterOptions {
+			bosses_only: true,
+			..Default::default()
+		};
+		let encs = feed_with(&log, vec![], opts).await;
+		assert_eq!(encs.all.len(), 2);
+		assert!(encs.all.iter().all(|e| e.is_boss()));
+	}
+
+	#[cfg(feature = "serde")]
+	#[tokio::test]
+	async fn json_roundtrip() {
+		let encs = feed(BRONTES, vec![]).await;
+		let js = serde_json::to_string(&encs.all).unwrap();
+		let back: Vec<Encounter> = serde_json::from_str(&js).unwrap();
+		assert_eq!(back.len(), 2);
+		assert_eq!(back[1].status(), encs.all[1].status());
+		assert_eq!(back[1].me().unwrap().all_dmg_out().total, 1000);
+	}
 }
@@ -3,6 +3,7 @@ use super::{Action, Actor, NamedID};
 use chrono::NaiveTime;
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Line {
 	pub ts: NaiveTime,
 	pub source: Option<Actor>,
@@ -11,23 +12,19 @@ pub struct Line {
 }
 
 impl Line {
+	// None if l is not a valid log line
 	pub fn new(l: &str) -> Option<Self> {
-		// println!("{}", l);
 		let l = l.replace("[HIDDEN]", "");
 		let mut parts = l.splitn(6, ']').map(|s| s.trim().trim_start_matches('['));
-		let ts = NaiveTime::parse_from_str(parts.next().unwrap(), "%H:%M:%S.%3f")
-			.unwrap_or_else(|_| panic!("{}", l));
-
-		let source = Actor::new(parts.next().unwrap());
-		let target = Actor::new(parts.next().unwrap());
-		let ability = NamedID::new(parts.next().unwrap());
-		let act = parts.next().unwrap();
-		let val = parts.next().unwrap();
-		let action = Action::new(act, val, ability, &target);
-		// let value = Value::new(parts.next().unwrap(), &action);
-
-		// dbg!(l, action);
-		// dbg!(&parts[5]);
+		let ts = NaiveTime::parse_from_str(parts.next()?, "%H:%M:%S.%3f").ok()?;
+
+		let source = actor(parts.next()?)?;
+		let target = actor(parts.next()?)?;
+		let ability = NamedID::new(parts.next()?);
+		let act = parts.next()?;
+		let val = parts.next()?;
+		let action = Action::new(act, val, ability, &target)?;
+
 		Some(Line {
 			ts,
 			source,
@@ -36,3 +33,50 @@ impl Line {
 		})
 	}
 }
+
+// Some(None) for an empty actor, None if it can't be parsed
+fn actor(p: &str) -> Option<Option<Actor>> {
+	if p.is_empty() || p == "=" {
+		return Some(None);
+	}
+	Actor::new(p).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	#[test]
+	fn invalid_lines() {
+		assert!(Line::new("[20:00:00.000] [@Tank#1|(0,0,0,0)|(100/100)] [] [] [Event {836045448945472}: EnterCombat {836045448945489}]").is_some());
+		assert!(Line::new("").is_none());
+		assert!(Line::new("garbage").is_none());
+		assert!(Line::new("[25:99:00.000] [] [] [] [] ()").is_none());
+		assert!(Line::new("[20:00:00.000] [@Tank#x|(0,0,0,0)|(100/100)] [] [] [] ()").is_none());
+		assert!(Line::new("[20:00:00.000] [@Tank#1|(0,0,0,0)] [] []").is_none());
+	}
+
+	#[test]
+	fn malformed_lines() {
+		let ok = "[20:00:00.000] [@Tank#1|(0,0,0,0)|(100/100)] [Dread Master Brontes {3266825467691008}:1|(0,0,0,0)|(200/1000)] [Strike {1}] [ApplyEffect {836045448945477}: Damage {836045448945501}] (300 ~250 kinetic {836045448940873} -shield {836045448945509} (50 absorbed {836045448945511})) <300>";
+		assert!(Line::new(ok).is_some());
+
+		for (what, from, to) in [
+			("tilde", "~250", "~x"),
+			("value id", "kinetic {836045448940873}", "kinetic {abc"),
+			("value id", "kinetic {836045448940873}", "kinetic {abc}"),
+			("absorbed", "(50 absorbed", "(5.5 absorbed"),
+			("threat", "<300>", "(<)"),
+			("player", "@Tank#1|", "#1|"),
+			("player", "@Tank#1|", "@Tank/Khem Val {3}#1|"),
+		] {
+			let l = ok.replace(from, to);
+			assert_ne!(l, ok, "{what}");
+			assert!(Line::new(&l).is_none(), "{what}: {l}");
+		}
+
+		// ids in the wrong order are no id, like a missing one
+		let l = Line::new(&ok.replace("{3266825467691008}:1", "}3266825467691008{:1")).unwrap();
+		assert_eq!(l.target.unwrap().id.id, 0);
+	}
+}
@@ -0,0 +1,253 @@
+use std::{
+	collections::{hash_map::DefaultHasher, HashMap},
+	hash::{Hash, Hasher},
+};
+
+use chrono::{Duration, NaiveTime, Timelike};
+
+use super::{align, encounter::Encounters, reader::read_lines, *};
+
+// how far apart the same event can be in two logs once they are aligned
+const TOLERANCE_MS: i64 = 500;
+// how far apart the EnterCombat of the same fight can be in two logs
+const MATCH_WINDOW_SECS: i64 = 30;
+
+// merges the logs of several raid members, the first log is the reference: its clock,
+// areas and combat start / end are used, the other logs only add the lines it is missing.
+// the other logs are moved to the reference's clock using align::estimate, or by matching
+// EnterCombat if they have no events in common
+pub fn merge_files(paths: &[&str]) -> std::io::Result<Vec<Encounter>> {
+	let logs = paths
+		.iter()
+		.map(|p| read_lines(p))
+		.collect::<std::io::Result<Vec<_>>>()?;
+	Ok(merge(logs))
+}
+
+pub fn merge(logs: Vec<Vec<Line>>) -> Vec<Encounter> {
+	let mut encs = Encounters::new();
+	for l in merge_lines(logs) {
+		encs.push(l, &|_, _| {});
+	}
+	encs.finish()
+}
+
+pub fn merge_lines(logs: Vec<Vec<Line>>) -> Vec<Line> {
+	let mut logs = logs.into_iter();
+	let Some(reference) = logs.next() else {
+		return Vec::new();
+	};
+	let starts = combat_starts(&reference);
+
+	let others = logs
+		.map(|log| {
+			let skew = align::estimate(&reference, &log).map(|o| o.offset);
+			align_to(&starts, log, skew)
+		})
+		.collect::<Vec<_>>();
+
+	let first = reference.first().map(|l| l.ts);
+	let mut all = clock(first, reference)
+		.map(|(ms, l)| (ms, 0, l))
+		.collect::<Vec<_>>();
+	for (i, log) in others.into_iter().enumerate() {
+		all.extend(clock(first, log).map(|(ms, l)| (ms, i + 1, l)));
+	}
+	// stable, so on equal timestamps the reference comes first
+	all.sort_by_key(|(ms, i, _)| (*ms, *i));
+
+	let mut seen = HashMap::<u64, Vec<(i64, u64)>>::new();
+	all.into_iter()
+		.filter(|(ms, i, l)| {
+			let bit = 1u64 << (*i).min(63);
+			let entries = seen.entry(signature(l)).or_default();
+			entries.retain(|(at, _)| ms - at <= TOLERANCE_MS);
+			// the same event is in each log once, so a repeat from the same log is a new event
+			match entries.iter_mut().find(|(_, logs)| logs & bit == 0) {
+				Some((_, logs)) => {
+					*logs |= bit;
+					false
+				}
+				None => {
+					entries.push((*ms, bit));
+					true
+				}
+			}
+		})
+		.map(|(_, _, l)| l)
+		.collect()
+}
+
+// milliseconds since midnight of the day the reference starts, a log going past midnight
+// keeps counting up instead of starting over at 0
+fn clock(first: Option<NaiveTime>, log: Vec<Line>) -> impl Iterator<Item = (i64, Line)> {
+	let half_day = Duration::hours(12);
+	let ms = |t: NaiveTime| {
+		t.num_seconds_from_midnight() as i64 * 1000 + t.nanosecond() as i64 / 1_000_000
+	};
+	// a log starting well before the reference in the day started after midnight
+	let mut day = match (first, log.first()) {
+		(Some(f), Some(l)) if f - l.ts > half_day => 1,
+		_ => 0,
+	};
+	let mut prev = log.first().map(|l| l.ts);
+	log.into_iter().map(move |l| {
+		if prev.is_some_and(|p| p - l.ts > half_day) {
+			day += 1;
+		}
+		prev = Some(l.ts);
+		(day * 86_400_000 + ms(l.ts), l)
+	})
+}
+
+fn combat_starts(log: &[Line]) -> Vec<NaiveTime> {
+	log.iter()
+		.filter(|l| matches!(l.action, Action::EnterCombat))
+		.map(|l| l.ts)
+		.collect()
+}
+
+// shifts every fight in log by skew, or to the closest EnterCombat in starts if the skew is
+// unknown, drops the lines of fights the reference did not see and the lines that start or
+// end an encounter
+fn align_to(starts: &[NaiveTime], log: Vec<Line>, skew: Option<Duration>) -> Vec<Line> {
+	let window = Duration::seconds(MATCH_WINDOW_SECS);
+	let mut offset = None;
+	let mut out = Vec::with_capacity(log.len());
+	for mut l in log {
+		match l.action {
+			Action::EnterCombat => {
+				let ts = l.ts + skew.unwrap_or(Duration::zero());
+				offset = starts
+					.iter()
+					.map(|s| around_midnight(*s - ts))
+					.filter(|d| d.abs() <= window)
+					.min_by_key(|d| d.abs())
+					.map(|d| skew.unwrap_or(d));
+				continue;
+			}
+			Action::ExitCombat | Action::AreaEntered { .. } => continue,
+			_ => {}
+		}
+		if let Some(d) = offset {
+			l.ts += d;
+			out.push(l);
+		}
+	}
+	out
+}
+
+// the shortest way from one time of day to another, 23:59 to 00:01 is 2 minutes and not
+// minus a day
+pub(crate) fn around_midnight(d: Duration) -> Duration {
+	if d > Duration::hours(12) {
+		d - Duration::days(1)
+	} else if d < -Duration::hours(12) {
+		d + Duration::days(1)
+	} else {
+		d
+	}
+}
+
+// the same event seen by two players, health and position can differ between the logs and
+// so can the effective healing, it depends on the target's health
+pub(crate) fn signature(l: &Line) -> u64 {
+	let mut h = DefaultHasher::new();
+	for a in [&l.source, &l.target] {
+		a.as_ref().map(|a| (&a.id, &a.typ)).hash(&mut h);
+	}
+	match &l.action {
+		Action::Heal {
+			ability,
+			value,
+			critical,
+			..
+		} => ("heal", ability, value, critical).hash(&mut h),
+		a => a.hash(&mut h),
+	}
+	h.finish()
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	use crate::fixtures::BRONTES;
+
+	fn lines(log: &str) -> Vec<Line> {
+		log.lines().map(|l| Line::new(l).unwrap()).collect()
+	}
+
+	#[test]
+	fn merge_two_logs() {
+		let tank = lines(BRONTES);
+		// two seconds behind, sees the tank's hit and heals someone the tank can't see
+		let heals = lines("\
+[19:59:58.000] [@Heals#2|(0,0,0,0)|(100/100)] [] [] [AreaEntered {836045448953664}: The Dread Fortress {833571547775668} 8 Player Veteran {836045448953652}]
+[19:59:59.000] [@Heals#2|(0,0,0,0)|(100/100)] [] [] [Event {836045448945472}: EnterCombat {836045448945489}]
+[20:00:00.100] [@Tank#1|(0,0,0,0)|(90/100)] [Dread Master Brontes {3266825467691008}:1|(0,0,0,0)|(770/1000)] [Strike {1}] [ApplyEffect {836045448945477}: Damage {836045448945501}] (230 kinetic {836045448940873})
+[20:00:01.000] [@Heals#2|(0,0,0,0)|(100/100)] [@Dps#3|(0,0,0,0)|(50/100)] [Heal {3}] [ApplyEffect {836045448945477}: Heal {836045448945500}] (500)
+[20:00:02.000] [@Heals#2|(0,0,0,0)|(100/100)] [] [] [Event {836045448945472}: ExitCombat {836045448945490}]
+");
+
+		let merged = merge_lines(vec![tank.clone(), heals.clone()]);
+		assert_eq!(merged.len(), tank.len() + 1);
+
+		let encs = merge(vec![tank, heals]);
+		assert_eq!(encs.len(), 2);
+		let e = &encs[0];
+		assert_eq!(e.name(), "Brontes (8m Veteran)");
+		let tank = e.players.iter().find(|p| p.id.name == "Tank").unwrap();
+		assert_eq!(tank.all_dmg_out().total, 230);
+		let healer = e.players.iter().find(|p| p.id.name == "Heals").unwrap();
+		assert_eq!(healer.all_heal_out().total, 500);
+	}
+
+	#[test]
+	fn past_midnight() {
+		let tank = lines("\
+[23:59:58.000] [@Tank#1|(0,0,0,0)|(100/100)] [] [] [Event {836045448945472}: EnterCombat {836045448945489}]
+[23:59:59.000] [@Tank#1|(0,0,0,0)|(100/100)] [Dread Master Brontes {3266825467691008}:1|(0,0,0,0)|(770/1000)] [Strike {1}] [ApplyEffect {836045448945477}: Damage {836045448945501}] (230 kinetic {836045448940873})
+[00:00:01.000] [@Tank#1|(0,0,0,0)|(100/100)] [Dread Master Brontes {3266825467691008}:1|(0,0,0,0)|(540/1000)] [Strike {1}] [ApplyEffect {836045448945477}: Damage {836045448945501}] (230 kinetic {836045448940873})
+[00:00:03.000] [@Tank#1|(0,0,0,0)|(100/100)] [] [] [Event {836045448945472}: ExitCombat {836045448945490}]
+");
+		// a second behind, heals the tank when it's at full health in the tank's log
+		let heals = lines("\
+[23:59:57.000] [@Heals#2|(0,0,0,0)|(100/100)] [] [] [Event {836045448945472}: EnterCombat {836045448945489}]
+[23:59:58.000] [@Tank#1|(0,0,0,0)|(90/100)] [Dread Master Brontes {3266825467691008}:1|(0,0,0,0)|(770/1000)] [Strike {1}] [ApplyEffect {836045448945477}: Damage {836045448945501}] (230 kinetic {836045448940873})
+[23:59:59.500] [@Heals#2|(0,0,0,0)|(100/100)] [@Tank#1|(0,0,0,0)|(90/100)] [Heal {3}] [ApplyEffect {836045448945477}: Heal {836045448945500}] (500)
+[00:00:00.000] [@Tank#1|(0,0,0,0)|(90/100)] [Dread Master Brontes {3266825467691008}:1|(0,0,0,0)|(540/1000)] [Strike {1}] [ApplyEffect {836045448945477}: Damage {836045448945501}] (230 kinetic {836045448940873})
+[00:00:02.000] [@Heals#2|(0,0,0,0)|(100/100)] [] [] [Event {836045448945472}: ExitCombat {836045448945490}]
+");
+		let mut tank_view = tank.clone();
+		tank_view.insert(2, Line::new("[00:00:00.500] [@Heals#2|(0,0,0,0)|(100/100)] [@Tank#1|(0,0,0,0)|(100/100)] [Heal {3}] [ApplyEffect {836045448945477}: Heal {836045448945500}] (500)").unwrap());
+
+		let merged = merge_lines(vec![tank_view.clone(), heals.clone()]);
+		// the heal is in both logs with a different effective amount, it's still one event
+		assert_eq!(merged.len(), tank_view.len());
+		let times = merged.iter().map(|l| l.ts.to_string()).collect::<Vec<_>>();
+		assert_eq!(
+			times,
+			vec![
+				"23:59:58",
+				"23:59:59",
+				"00:00:00.500",
+				"00:00:01",
+				"00:00:03"
+			]
+		);
+
+		// without the heal in the tank's log it comes from the healer's, after midnight
+		let merged = merge_lines(vec![tank.clone(), heals.clone()]);
+		assert_eq!(merged[2].ts.to_string(), "00:00:00.500");
+		let encs = merge(vec![tank, heals]);
+		assert_eq!(encs.len(), 1);
+		let tank = encs[0]
+			.players
+			.iter()
+			.find(|p| p.id.name == "Tank")
+			.unwrap();
+		assert_eq!(tank.all_dmg_out().total, 460);
+		assert_eq!(tank.all_heal_in().total, 500);
+	}
+}
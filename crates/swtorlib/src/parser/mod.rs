@@ -1,5 +1,7 @@
 use tokio::sync::mpsc::Receiver;
 
+use reader::Start;
+
 pub mod action;
 use action::*;
 
@@ -16,19 +18,67 @@ pub mod namedid;
 use namedid::*;
 
 pub mod actor_stats;
+pub mod align;
+pub mod bests;
+pub mod bosses;
+pub mod catalog;
 
 pub mod consts;
+pub mod discipline;
+pub mod merge;
+pub mod phase;
 pub mod reader;
+pub mod role;
 pub mod sorted_vec;
+pub mod split;
+pub mod trigger;
 pub mod utils;
 
 pub async fn parse<F: Fn(&Encounter, &Line)>(dir: &str, process: F) -> std::io::Result<()> {
-	let mut rx = reader::Reader::process_dir(dir).await.unwrap();
-	let mut enc = Encounters::new();
+	parse_with(dir, Encounters::new(), Start::Beginning, process).await
+}
+
+// like parse, with phases, options or a webhook already set on enc. the webhook only posts
+// encounters that end after the lines already in the log were read
+pub async fn parse_with<F: Fn(&Encounter, &Line)>(
+	dir: &str,
+	mut enc: Encounters,
+	start: Start,
+	process: F,
+) -> std::io::Result<()> {
+	let (mut rx, caught_up) = reader::Reader::follow_dir(dir, start).await?;
+	enc.set_caught_up(caught_up);
 	let h = enc.process(&mut rx, process).await;
 	Ok(h)
 }
 
+// like parse_with, for one log instead of the newest one in a directory
+pub async fn parse_log<F: Fn(&Encounter, &Line)>(
+	path: &str,
+	mut enc: Encounters,
+	start: Start,
+	process: F,
+) -> std::io::Result<()> {
+	let mut rx = reader::Reader::process_file(path, start).await?;
+	enc.process(&mut rx, process).await;
+	Ok(())
+}
+
+// parses a whole log file at once, an encounter still in progress at the end of the log is
+// left out
+pub fn parse_file(path: &str) -> std::io::Result<Vec<Encounter>> {
+	let mut enc = Encounters::new();
+	for l in reader::read_lines(path)? {
+		enc.push(l, &|_, _| {});
+	}
+	Ok(enc.finished())
+}
+
+// <config dir>/swtormeter, where alerts.toml, phases.toml and webhook.toml are looked for
+pub fn config_dir() -> Option<std::path::PathBuf> {
+	Some(dirs_next::config_dir()?.join("swtormeter"))
+}
+
 pub static BASE_COMBATLOGS_DIR: &str = "/Documents/Star Wars - The Old Republic/CombatLogs/";
 
 pub fn logs_path() -> Option<String> {
@@ -55,6 +105,22 @@ pub fn logs_path() -> Option<String> {
 mod tests {
 
 	use super::*;
+	use crate::fixtures::BRONTES;
+
+	#[test]
+	fn unfinished_encounters() {
+		let path =
+			std::env::temp_dir().join(format!("swtormeter-parse-{}.txt", std::process::id()));
+		// the log ends in the middle of the kill
+		let log = BRONTES.lines().take(8).collect::<Vec<_>>().join("\n");
+		std::fs::write(&path, log).unwrap();
+		let encs = parse_file(path.to_str().unwrap()).unwrap();
+		std::fs::remove_file(&path).unwrap();
+
+		assert_eq!(encs.len(), 1);
+		assert_eq!(encs[0].pull, 1);
+	}
+
 	#[tokio::test]
 	async fn parse_test() {
 		dbg!(logs_path());
@@ -9,6 +9,7 @@ use crate::parser::utils::*;
 // }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NamedID {
 	pub id: u64,
 	pub name: String,
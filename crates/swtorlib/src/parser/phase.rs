@@ -0,0 +1,180 @@
+use chrono::{Duration, NaiveTime};
+
+use std::collections::HashMap;
+use std::ops::Sub;
+
+#[cfg(feature = "serde")]
+use std::path::Path;
+
+use super::actor_stats::{ActorStats, Meter};
+use super::encounter::{
+	new_sorted_by_dmg, new_sorted_by_health, update_actors, Encounter, EncounterOptions,
+};
+use super::sorted_vec::SortedVec;
+pub use super::trigger::Trigger;
+use super::*;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PhaseDef {
+	pub name: String,
+	// only used for encounters against this boss (as named in the boss database)
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub boss: Option<String>,
+	pub trigger: Trigger,
+}
+
+impl PhaseDef {
+	pub fn new(name: &str, trigger: Trigger) -> Self {
+		Self {
+			name: name.to_owned(),
+			boss: None,
+			trigger,
+		}
+	}
+
+	pub fn for_boss(mut self, boss: &str) -> Self {
+		self.boss = Some(boss.to_owned());
+		self
+	}
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+pub struct PhaseDefs {
+	#[cfg_attr(feature = "serde", serde(default, rename = "phase"))]
+	pub defs: Vec<PhaseDef>,
+}
+
+#[cfg(feature = "serde")]
+impl PhaseDefs {
+	// [[phase]]
+	// name = "Burn"
+	// boss = "Brontes"
+	// trigger = { type = "health_below", npc = 3266825467691008, pct = 30.0 }
+	pub fn from_toml(data: &str) -> std::io::Result<Self> {
+		toml::from_str(data).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+	}
+
+	// phases.toml in dir
+	pub fn find(dir: &Path) -> Option<String> {
+		let path = dir.join("phases.toml");
+		path.is_file().then(|| path.display().to_string())
+	}
+
+	pub fn load(path: &str) -> std::io::Result<Self> {
+		Self::from_toml(&std::fs::read_to_string(path)?)
+	}
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Phase {
+	pub name: String,
+	// index of the PhaseDef that started this phase, None for the opening phase
+	pub def: Option<usize>,
+	pub start: NaiveTime,
+	pub ts: NaiveTime,
+	pub end: NaiveTime,
+
+	// the same stats as the encounter's, for the lines of this phase only
+	pub players: SortedVec<ActorStats>,
+	pub npcs: SortedVec<ActorStats>,
+}
+
+impl Phase {
+	pub fn new(name: &str, def: Option<usize>, start: NaiveTime) -> Self {
+		Self {
+			name: name.to_owned(),
+			def,
+			start,
+			ts: start,
+			players: new_sorted_by_dmg(),
+			npcs: new_sorted_by_health(),
+			..Default::default()
+		}
+	}
+
+	pub fn append(
+		&mut self,
+		l: &Line,
+		opts: &EncounterOptions,
+		disciplines: &HashMap<NamedID, (NamedID, NamedID)>,
+	) {
+		self.ts = l.ts;
+		update_actors(&mut self.players, &mut self.npcs, l, opts, disciplines);
+	}
+
+	pub fn elapsed(&self) -> Duration {
+		if self.end != NaiveTime::MIN {
+			self.end.sub(self.start)
+		} else {
+			self.ts.sub(self.start)
+		}
+	}
+
+	pub fn player(&self, id: &NamedID) -> Option<&ActorStats> {
+		self.players.iter().find(|p| &p.id == id)
+	}
+
+	fn vec_for<F: Fn(&ActorStats) -> Meter>(&self, fn_: F) -> Vec<(Vec<String>, f64)> {
+		let elapsed = self.elapsed().num_seconds().max(1);
+		Encounter::get_vec_for(self.players.iter(), elapsed, fn_)
+	}
+
+	pub fn heals_out(&self) -> Vec<(Vec<String>, f64)> {
+		self.vec_for(ActorStats::all_heal_out)
+	}
+
+	pub fn dmg_out(&self) -> Vec<(Vec<String>, f64)> {
+		self.vec_for(ActorStats::all_dmg_out)
+	}
+
+	pub fn spells_out(&self) -> Vec<(Vec<String>, f64)> {
+		self.vec_for(ActorStats::all_spells_out)
+	}
+
+	pub fn heals_in(&self) -> Vec<(Vec<String>, f64)> {
+		self.vec_for(ActorStats::all_heal_in)
+	}
+
+	pub fn dmg_in(&self) -> Vec<(Vec<String>, f64)> {
+		self.vec_for(ActorStats::all_dmg_in)
+	}
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+
+	use super::*;
+
+	#[test]
+	fn defs_from_toml() {
+		let defs = PhaseDefs::from_toml(
+			r#"
+			[[phase]]
+			name = "Burn"
+			boss = "Brontes"
+			trigger = { type = "health_below", npc = 3266825467691008, pct = 30.0 }
+
+			[[phase]]
+			name = "Adds"
+			trigger = { type = "npc_spawn", npc = 3273941900591105 }
+			"#,
+		)
+		.unwrap()
+		.defs;
+		assert_eq!(defs.len(), 2);
+		assert_eq!(defs[0].boss.as_deref(), Some("Brontes"));
+		assert!(defs[0].trigger.once());
+		assert_eq!(
+			defs[1],
+			PhaseDef::new(
+				"Adds",
+				Trigger::NpcSpawn {
+					npc: 3273941900591105
+				}
+			)
+		);
+	}
+}
@@ -1,20 +1,63 @@
 use tokio::sync::Mutex;
 
-use std::fs::read_dir;
-use std::io::Result;
-use std::path::Path;
+use chrono::NaiveTime;
+use std::io::{Error, ErrorKind, Result, SeekFrom};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::fs::File;
-use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::io::{AsyncBufReadExt, AsyncSeekExt, BufReader};
 use tokio::sync::mpsc::{channel, Receiver, Sender};
 use tokio::time::sleep;
 
-use super::Line;
+use super::{catalog::log_files, Line};
+use crate::metrics::Metrics;
+
+// where to start reading the first log
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Start {
+	#[default]
+	Beginning,
+	// only lines written from now on
+	End,
+}
+
+// how many lines had been sent when the reader first got to the end of the log, the lines
+// after that were written while it was following the log
+#[derive(Debug, Clone)]
+pub struct CaughtUp(Arc<AtomicUsize>);
+
+impl Default for CaughtUp {
+	fn default() -> Self {
+		Self(Arc::new(AtomicUsize::new(usize::MAX)))
+	}
+}
+
+impl CaughtUp {
+	pub fn done(&self) -> bool {
+		self.0.load(Ordering::Relaxed) != usize::MAX
+	}
+
+	// n counts from 1, like the lines sent
+	pub fn is_live(&self, n: usize) -> bool {
+		n > self.0.load(Ordering::Relaxed)
+	}
+
+	pub(crate) fn reached(&self, sent: usize) {
+		let _ = self
+			.0
+			.compare_exchange(usize::MAX, sent, Ordering::Relaxed, Ordering::Relaxed);
+	}
+}
 
 pub struct Reader;
 impl Reader {
-	pub async fn process_dir(dir: &str) -> Result<Receiver<Line>> {
+	pub async fn process_dir(dir: &str, start: Start) -> Result<Receiver<Line>> {
+		Ok(Self::follow_dir(dir, start).await?.0)
+	}
+
+	// like process_dir, telling when the lines that were already logged have been sent
+	pub async fn follow_dir(dir: &str, start: Start) -> Result<(Receiver<Line>, CaughtUp)> {
 		//let name = &fp[fp.find("combat_").unwrap() + 7..fp.find(".txt").unwrap()];
 
 		let (tx, rx) = channel::<Line>(8);
@@ -30,23 +73,61 @@ impl Reader {
 		let fp = fpath.clone();
 		tokio::spawn(async move { Self::watch_dir(dir.to_owned(), fp).await });
 		let fp = fpath.clone();
-		tokio::spawn(async move { Self::process(tx, fp).await });
+		let caught_up = CaughtUp::default();
+		let cu = caught_up.clone();
+		tokio::spawn(async move { Self::process(tx, fp, start, cu).await });
 
+		Ok((rx, caught_up))
+	}
+
+	// reads and follows one log instead of the newest one in a directory
+	pub async fn process_file(path: &str, start: Start) -> Result<Receiver<Line>> {
+		let (tx, rx) = channel::<Line>(8);
+		let fpath = Arc::new(Mutex::new(path.to_owned()));
+		tokio::spawn(async move { Self::process(tx, fpath, start, CaughtUp::default()).await });
+		Ok(rx)
+	}
+
+	// sends a finished log's lines as fast as they were logged times speed, lines before
+	// from are sent at once. speed <= 0 sends everything at once
+	pub async fn replay_file(
+		path: &str,
+		speed: f64,
+		from: Option<NaiveTime>,
+	) -> Result<Receiver<Line>> {
+		let lines = read_lines(path)?;
+		let (tx, rx) = channel::<Line>(8);
+		tokio::spawn(async move {
+			let mut prev: Option<NaiveTime> = None;
+			for l in lines {
+				// no waiting until both lines are past from
+				let skipped = |ts: &NaiveTime| from.is_some_and(|t| *ts < t);
+				if let Some(wait) = prev.filter(|p| speed > 0. && !skipped(p)).map(|p| l.ts - p) {
+					let ms = wait.num_milliseconds().max(0) as f64 / speed;
+					sleep(Duration::from_millis(ms as u64)).await;
+				}
+				prev = Some(l.ts);
+				if tx.send(l).await.is_err() {
+					return;
+				}
+			}
+		});
 		Ok(rx)
 	}
 
 	async fn watch_dir(dir: String, ff: Arc<Mutex<String>>) {
-		loop {
-			{
-				let (fp, _) = latest_log(dir.as_str()).unwrap();
+		// stops once process is gone
+		while Arc::strong_count(&ff) > 1 {
+			if let Ok((fp, _)) = latest_log(dir.as_str()) {
 				*ff.lock().await = fp;
 			}
 			sleep(Duration::from_secs(10)).await
 		}
 	}
 
-	async fn process(tx: Sender<Line>, ff: Arc<Mutex<String>>) {
+	async fn process(tx: Sender<Line>, ff: Arc<Mutex<String>>, start: Start, caught_up: CaughtUp) {
 		let mut buf = Vec::with_capacity(1024);
+		let mut sent = 0;
 		let mut fname: Option<String> = None;
 		let mut f: Option<File> = None;
 		loop {
@@ -55,8 +136,13 @@ impl Reader {
 				if !fp.is_empty() {
 					let fp = fp.clone();
 					if fname.is_none() || fname.clone().unwrap() != fp {
-						fname = Some(fp.clone());
-						f = Some(File::open(fp.clone()).await.unwrap());
+						if let Ok(mut file) = File::open(fp.clone()).await {
+							if fname.is_none() && start == Start::End {
+								let _ = file.seek(SeekFrom::End(0)).await;
+							}
+							fname = Some(fp.clone());
+							f = Some(file);
+						}
 					}
 				}
 
@@ -75,33 +161,61 @@ impl Reader {
 				if ln == 0 {
 					break;
 				}
-				// the log uses a weird encoding
-				let s: String = buf.iter().map(|&c| c as char).collect();
+				let s = decode(&buf);
 				if !s.ends_with('\n') {
 					break;
 				}
 				let s = &s.clone();
-				for ss in s.trim().lines() {
-					if let Some(l) = Line::new(ss.trim()) {
-						tx.send(l).await.unwrap();
+				for ss in s.trim().lines().map(|l| l.trim()) {
+					match Line::new(ss) {
+						Some(l) => {
+							Metrics::global().line_parsed();
+							if tx.send(l).await.is_err() {
+								return;
+							}
+							sent += 1;
+						}
+						None if !ss.is_empty() => Metrics::global().parse_error(),
+						None => {}
 					}
 				}
 				buf.clear();
 			}
+			caught_up.reached(sent);
 			sleep(Duration::from_millis(500)).await
 		}
 	}
 }
 
-pub fn latest_log(dir: &str) -> std::io::Result<(String, String)> {
-	let paths = read_dir(dir)?;
-	let mut paths: Vec<_> = paths
-		.map(|p| p.unwrap().path().display().to_string())
-		.collect();
-	paths.sort();
+// the log uses a weird encoding
+pub fn decode(buf: &[u8]) -> String {
+	buf.iter().map(|&c| c as char).collect()
+}
 
-	let path = paths.get(paths.len() - 1).unwrap();
-	let name = Path::new(&path).file_name().unwrap().to_str().unwrap();
+// the reverse of decode, for text that came from decode
+pub fn encode(s: &str) -> Vec<u8> {
+	s.chars().map(|c| u8::try_from(c).unwrap_or(b'?')).collect()
+}
+
+pub fn read_lines(path: &str) -> Result<Vec<Line>> {
+	let buf = std::fs::read(path)?;
+	Ok(decode(&buf)
+		.lines()
+		.map(|l| l.trim())
+		.filter(|l| !l.is_empty())
+		.filter_map(Line::new)
+		.collect())
+}
 
-	Ok((path.to_owned(), name.to_owned()))
+// the path and file name of the newest log in dir
+pub fn latest_log(dir: &str) -> Result<(String, String)> {
+	let path = log_files(dir)?
+		.pop()
+		.ok_or_else(|| Error::new(ErrorKind::NotFound, format!("no logs in {dir}")))?;
+	let name = path
+		.file_name()
+		.unwrap_or_default()
+		.to_string_lossy()
+		.to_string();
+	Ok((path.display().to_string(), name))
 }
@@ -0,0 +1,27 @@
+use super::{discipline::Discipline, namedid::NamedID};
+
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Role {
+	Tank,
+	Healer,
+	Dps,
+
+	#[default]
+	Unknown,
+}
+
+impl Role {
+	pub fn from_spec(spec: &NamedID) -> Self {
+		Discipline::find(spec).map_or(Self::Unknown, |d| d.role)
+	}
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			Self::Tank => "Tank",
+			Self::Healer => "Healer",
+			Self::Dps => "DPS",
+			Self::Unknown => "Unknown",
+		}
+	}
+}
@@ -31,7 +31,7 @@ impl<T: Debug> SortedVec<T> {
 		self.v.sort_by(self.cmp.as_ref());
 	}
 
-	pub fn iter(&self) -> Iter<T> {
+	pub fn iter(&self) -> Iter<'_, T> {
 		self.v.iter()
 	}
 
@@ -70,3 +70,22 @@ impl<T: Debug + Clone> Clone for SortedVec<T> {
 		}
 	}
 }
+
+// serialized as a plain list, a deserialized SortedVec keeps the order it was saved in
+// but loses its sort function
+#[cfg(feature = "serde")]
+impl<T: Debug + serde::Serialize> serde::Serialize for SortedVec<T> {
+	fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+		self.v.serialize(s)
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Debug + serde::Deserialize<'de>> serde::Deserialize<'de> for SortedVec<T> {
+	fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+		Ok(Self {
+			v: Vec::deserialize(d)?,
+			..Self::default()
+		})
+	}
+}
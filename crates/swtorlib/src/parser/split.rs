@@ -0,0 +1,171 @@
+use std::{io, path::Path};
+
+use super::{
+	catalog::log_time,
+	encounter::Encounters,
+	reader::{decode, encode},
+	*,
+};
+
+// one encounter's raw lines, with the AreaEntered and DisciplineChanged lines it needs to
+// parse on its own
+#[derive(Debug, Clone, PartialEq)]
+pub struct Piece {
+	pub name: String,
+	pub text: String,
+}
+
+// cuts a log into one piece per encounter, from EnterCombat to ExitCombat. lines between
+// encounters are dropped except for the context, line endings are kept as they are.
+// date prefixes the names, usually from log_date
+pub fn split(text: &str, date: &str) -> Vec<Piece> {
+	let mut encs = Encounters::new();
+	let mut area = "";
+	// the last DisciplineChanged of every player since the last AreaEntered
+	let mut specs: Vec<(u64, &str)> = Vec::new();
+	let mut open: Option<String> = None;
+	let mut pieces = Vec::new();
+
+	for raw in text.split_inclusive('\n') {
+		let Some(l) = Line::new(raw.trim()) else {
+			if let Some(p) = &mut open {
+				p.push_str(raw);
+			}
+			continue;
+		};
+
+		match &l.action {
+			Action::AreaEntered { .. } => {
+				area = raw;
+				specs.clear();
+			}
+			Action::DisciplineChanged { .. } => {
+				let id = l.source.as_ref().map_or(0, |s| s.id.id);
+				specs.retain(|(s, _)| *s != id);
+				specs.push((id, raw));
+			}
+			Action::EnterCombat => {
+				pieces.extend(open.take());
+				let mut p = area.to_owned();
+				specs.iter().for_each(|(_, s)| p.push_str(s));
+				open = Some(p);
+			}
+			_ => {}
+		}
+
+		let exit = matches!(l.action, Action::ExitCombat);
+		if let Some(p) = &mut open {
+			p.push_str(raw);
+			if exit {
+				pieces.extend(open.take());
+			}
+		}
+		encs.push(l, &|_, _| {});
+	}
+	pieces.extend(open);
+
+	// every EnterCombat starts exactly one encounter and one piece
+	pieces
+		.into_iter()
+		.zip(encs.finish())
+		.map(|(text, e)| {
+			let mut name = format!("{date}_{}_{}", e.start.format("%H-%M-%S"), clean(&e.area));
+			match e.boss_name() {
+				Some(b) => name += &format!("_{}_pull{}", clean(&b), e.pull),
+				None => name += "_trash",
+			}
+			Piece { name, text }
+		})
+		.collect()
+}
+
+// the date from combat_2026-10-18_20_00_00_000000.txt, or the file's name if it has none
+pub fn log_date(path: &str) -> String {
+	let path = Path::new(path);
+	let name = path.file_name().unwrap_or_default().to_string_lossy();
+	match log_time(&name) {
+		Some(t) => t.date().to_string(),
+		None => path
+			.file_stem()
+			.unwrap_or_default()
+			.to_string_lossy()
+			.to_string(),
+	}
+}
+
+// writes <out>/<name>.txt for every encounter in the log, returns the paths written
+pub fn split_file(path: &str, out: &str) -> io::Result<Vec<String>> {
+	let text = decode(&std::fs::read(path)?);
+	std::fs::create_dir_all(out)?;
+	split(&text, &log_date(path))
+		.into_iter()
+		.map(|p| {
+			let path = Path::new(out).join(p.name + ".txt");
+			std::fs::write(&path, encode(&p.text))?;
+			Ok(path.display().to_string())
+		})
+		.collect()
+}
+
+fn clean(s: &str) -> String {
+	let s = s.replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+	let s = s.trim_matches('_');
+	if s.is_empty() {
+		"unknown".to_owned()
+	} else {
+		s.to_owned()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	use crate::fixtures::BRONTES;
+
+	static VENGEANCE: &str = "[19:59:00.000] [@Tank#1|(0,0,0,0)|(100/100)] [] [] [DisciplineChanged {836045448953665}: Juggernaut {16141170711935532310}/Vengeance {2031339142381577}]";
+	static IMMORTAL: &str = "[20:00:00.500] [@Tank#1|(0,0,0,0)|(100/100)] [] [] [DisciplineChanged {836045448953665}: Juggernaut {16141170711935532310}/Immortal {2031339142381578}]";
+
+	// the fixture with a discipline change before and after entering the area, as the game
+	// writes it
+	fn log() -> String {
+		let (area, rest) = BRONTES.split_once('\n').unwrap();
+		format!("{VENGEANCE}\n{area}\n{IMMORTAL}\n{rest}").replace('\n', "\r\n")
+	}
+
+	#[test]
+	fn one_piece_per_encounter() {
+		let date = log_date("/logs/combat_2026-10-18_20_00_00_000000.txt");
+		assert_eq!(date, "2026-10-18");
+		assert_eq!(log_date("old.txt"), "old");
+
+		let pieces = split(&log(), &date);
+		let names = pieces.iter().map(|p| p.name.as_str()).collect::<Vec<_>>();
+		assert_eq!(
+			names,
+			vec![
+				"2026-10-18_20-00-01_The_Dread_Fortress_Brontes_pull1",
+				"2026-10-18_20-01-00_The_Dread_Fortress_Brontes_pull2",
+			]
+		);
+
+		let first = pieces[0].text.lines().collect::<Vec<_>>();
+		assert_eq!(first.len(), 7);
+		assert!(first[0].contains("AreaEntered"));
+		assert!(first[1].contains("Immortal"));
+		assert!(pieces[0]
+			.text
+			.ends_with("ExitCombat {836045448945490}]\r\n"));
+
+		// each piece parses on its own into the same encounter
+		let mut encs = Encounters::new();
+		for l in pieces[0].text.lines().filter_map(|l| Line::new(l.trim())) {
+			encs.push(l, &|_, _| {});
+		}
+		let encs = encs.finish();
+		assert_eq!(encs.len(), 1);
+		assert_eq!(encs[0].name(), "Brontes (8m Veteran)");
+		let tank = encs[0].players.iter().next().unwrap();
+		assert_eq!(tank.all_dmg_out().total, 230);
+	}
+}
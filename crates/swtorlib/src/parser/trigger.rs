@@ -0,0 +1,58 @@
+use super::*;
+
+// what starts a boss phase or an alert, in toml:
+//   trigger = { type = "health_below", npc = 3266825467691008, pct = 30.0 }
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(tag = "type", rename_all = "snake_case")
+)]
+pub enum Trigger {
+	// alerts only fire for effects on the local player (any player if it isn't known)
+	EffectApplied { effect: u64 },
+	// an ability activated by an npc, any npc if npc is not set
+	AbilityActivated { ability: u64, npc: Option<u64> },
+	// the npc's health dropped below pct (0 - 100), fires once per encounter
+	HealthBelow { npc: u64, pct: f64 },
+	NpcSpawn { npc: u64 },
+	NpcDeath { npc: u64 },
+}
+
+impl Trigger {
+	// spawned are the npc ids seen for the first time in this encounter on this line
+	pub fn matches(&self, l: &Line, spawned: &[u64]) -> bool {
+		match *self {
+			Self::EffectApplied { effect } => {
+				matches!(&l.action, Action::ApplyEffect(e) if e.id == effect)
+			}
+
+			Self::AbilityActivated { ability, npc } => {
+				matches!(&l.action, Action::AbilityActivate(a) if a.id == ability)
+					&& l.source
+						.as_ref()
+						.is_some_and(|a| a.is_npc() && npc.is_none_or(|id| a.id.id == id))
+			}
+
+			Self::HealthBelow { npc, pct } => {
+				[&l.source, &l.target].into_iter().flatten().any(|a| {
+					a.is_npc()
+						&& a.id.id == npc && a.max_health > 0
+						&& 100. * (a.health as f64 / a.max_health as f64) < pct
+				})
+			}
+
+			Self::NpcSpawn { npc } => spawned.contains(&npc),
+
+			Self::NpcDeath { npc } => {
+				matches!(l.action, Action::Death)
+					&& l.target.as_ref().is_some_and(|a| a.id.id == npc)
+			}
+		}
+	}
+
+	// health stays below the threshold once it got there, the others can happen again
+	pub fn once(&self) -> bool {
+		matches!(self, Self::HealthBelow { .. })
+	}
+}
@@ -1,3 +1,10 @@
+use std::{
+	borrow::Cow,
+	io::{self, Read, Write},
+};
+
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+
 pub fn extract_num(p: &str, l: char, r: char, right: bool) -> u64 {
 	if right {
 		extract_rpart(p, l, r).parse().unwrap_or(0)
@@ -7,20 +14,16 @@ pub fn extract_num(p: &str, l: char, r: char, right: bool) -> u64 {
 }
 
 pub fn extract_rpart(p: &str, l: char, r: char) -> &str {
-	if let Some(start) = p.rfind(l) {
-		if let Some(end) = p.rfind(r) {
-			return &p[start + 1..end];
-		}
+	if let (Some(start), Some(end)) = (p.rfind(l), p.rfind(r)) {
+		return p.get(start + 1..end).unwrap_or_default();
 	}
 
 	""
 }
 
 pub fn extract_lpart(p: &str, l: char, r: char) -> &str {
-	if let Some(start) = p.find(l) {
-		if let Some(end) = p.find(r) {
-			return &p[start + 1..end];
-		}
+	if let (Some(start), Some(end)) = (p.find(l), p.find(r)) {
+		return p.get(start + 1..end).unwrap_or_default();
 	}
 
 	""
@@ -48,6 +51,12 @@ pub fn fmt_num(n: f64) -> String {
 	}
 }
 
+// bodies smaller than this are not worth compressing
+const COMPRESS_MIN: usize = 256;
+// packets larger than this are rejected, compressed or not
+pub const MAX_PACKET: usize = 16 << 20;
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Packet<'a> {
 	id: [u8; 4],
 	code: u16,
@@ -60,9 +69,71 @@ pub struct Packet<'a> {
 
 impl<'a> Packet<'a> {
 	pub const SIZE: usize = 4 + 2 + 2 + 4 + 4 + 4;
+	pub const ID: [u8; 4] = *b"SWTM";
+
+	// body is sent as is, see encode for compressed bodies
+	pub fn new(code: u16, req: u16, sample: u32, body: &'a [u8]) -> Self {
+		Self {
+			id: Self::ID,
+			code,
+			req,
+			sample,
+			size: body.len() as u32,
+			size_uncompressed: body.len() as u32,
+			body: Some(body),
+		}
+	}
+
+	// the wire bytes for body, deflated if that makes it smaller
+	pub fn encode(code: u16, req: u16, sample: u32, body: &[u8]) -> Vec<u8> {
+		let compressed = if body.len() >= COMPRESS_MIN {
+			deflate(body).filter(|c| c.len() < body.len())
+		} else {
+			None
+		};
+		match compressed {
+			Some(c) => Packet {
+				size: c.len() as u32,
+				size_uncompressed: body.len() as u32,
+				..Packet::new(code, req, sample, &c)
+			}
+			.to_bytes(),
+			None => Packet::new(code, req, sample, body).to_bytes(),
+		}
+	}
+
+	// parses one packet from the start of buf, returns it with the number of bytes used
+	pub fn from_bytes(buf: &'a [u8]) -> io::Result<(Self, usize)> {
+		let Some(h) = buf.get(..Self::SIZE) else {
+			return Err(io::ErrorKind::UnexpectedEof.into());
+		};
+		let mut id = [0; 4];
+		id.copy_from_slice(&h[..4]);
+		if id != Self::ID {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "bad packet id"));
+		}
+		let u16_at = |i: usize| u16::from_be_bytes([h[i], h[i + 1]]);
+		let u32_at = |i: usize| u32::from_be_bytes([h[i], h[i + 1], h[i + 2], h[i + 3]]);
+
+		let size = u32_at(12);
+		let end = Self::SIZE + size as usize;
+		let Some(body) = buf.get(Self::SIZE..end) else {
+			return Err(io::ErrorKind::UnexpectedEof.into());
+		};
+		let p = Self {
+			id,
+			code: u16_at(4),
+			req: u16_at(6),
+			sample: u32_at(8),
+			size,
+			size_uncompressed: u32_at(16),
+			body: if size > 0 { Some(body) } else { None },
+		};
+		Ok((p, end))
+	}
 
 	pub fn to_bytes(&self) -> Vec<u8> {
-		let mut buf = Vec::with_capacity(24 + self.size as usize);
+		let mut buf = Vec::with_capacity(Self::SIZE + self.size as usize);
 		buf.extend_from_slice(&self.id);
 		buf.extend_from_slice(&self.code.to_be_bytes());
 		buf.extend_from_slice(&self.req.to_be_bytes());
@@ -74,6 +145,64 @@ impl<'a> Packet<'a> {
 		}
 		buf
 	}
+
+	pub fn code(&self) -> u16 {
+		self.code
+	}
+
+	pub fn req(&self) -> u16 {
+		self.req
+	}
+
+	pub fn sample(&self) -> u32 {
+		self.sample
+	}
+
+	// the total size of the packet on the wire
+	pub fn len(&self) -> usize {
+		Self::SIZE + self.size as usize
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.size == 0
+	}
+
+	pub fn is_compressed(&self) -> bool {
+		self.size != self.size_uncompressed
+	}
+
+	// the body, inflated if it was compressed
+	pub fn body(&self) -> io::Result<Cow<'a, [u8]>> {
+		let body = self.body.unwrap_or_default();
+		if !self.is_compressed() {
+			return Ok(Cow::Borrowed(body));
+		}
+		let size = self.size_uncompressed as usize;
+		if size > MAX_PACKET {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidData,
+				"packet too large",
+			));
+		}
+		// a body inflating to more than it claims is cut off right after
+		let mut out = Vec::with_capacity(size);
+		DeflateDecoder::new(body)
+			.take(size as u64 + 1)
+			.read_to_end(&mut out)?;
+		if out.len() != size {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidData,
+				"bad packet body size",
+			));
+		}
+		Ok(Cow::Owned(out))
+	}
+}
+
+fn deflate(body: &[u8]) -> Option<Vec<u8>> {
+	let mut enc = DeflateEncoder::new(Vec::new(), Compression::default());
+	enc.write_all(body).ok()?;
+	enc.finish().ok()
 }
 
 #[cfg(test)]
@@ -93,4 +222,42 @@ mod tests {
 		};
 		println!("{:?} {}", p.to_bytes(), Packet::SIZE);
 	}
+
+	#[test]
+	fn pkg_roundtrip() {
+		let body = "[20:00:00.000] [@Tank#1|(0,0,0,0)|(100/100)] []\n".repeat(20);
+		let mut buf = Packet::encode(3, 7, 42, body.as_bytes());
+		buf.extend(Packet::encode(1, 0, 43, b"hi"));
+
+		let (p, n) = Packet::from_bytes(&buf).unwrap();
+		assert!(p.is_compressed());
+		assert_eq!((p.code(), p.req(), p.sample()), (3, 7, 42));
+		assert_eq!(p.body().unwrap(), body.as_bytes());
+
+		let (p, m) = Packet::from_bytes(&buf[n..]).unwrap();
+		assert!(!p.is_compressed());
+		assert_eq!(&*p.body().unwrap(), b"hi");
+		assert_eq!(n + m, buf.len());
+
+		assert!(Packet::from_bytes(&buf[..n - 1]).is_err());
+		assert!(Packet::from_bytes(b"nope, not a packet at all").is_err());
+	}
+
+	#[test]
+	fn pkg_bad_sizes() {
+		let body = vec![b'x'; 4096];
+		let c = deflate(&body).unwrap();
+		let packet = |size_uncompressed| Packet {
+			size: c.len() as u32,
+			size_uncompressed,
+			..Packet::new(3, 0, 0, &c)
+		};
+
+		assert_eq!(packet(4096).body().unwrap(), body);
+		// claims to be huge, nothing is allocated for it
+		assert!(packet(u32::MAX).body().is_err());
+		// inflates to more than it claims
+		assert!(packet(100).body().is_err());
+		assert!(packet(8192).body().is_err());
+	}
 }
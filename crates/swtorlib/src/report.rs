@@ -0,0 +1,375 @@
+use std::io::{self, Write};
+
+use crate::parser::{
+	actor_stats::{ActorStats, MeterRow},
+	encounter::{Encounter, PlayerRow},
+	utils::fmt_num,
+};
+
+static STYLE: &str = "
+body { font-family: sans-serif; background: #1e1e1e; color: #ddd; margin: 2em; }
+a { color: #6cf; }
+table { border-collapse: collapse; margin: 0.5em 0 1em; }
+th, td { padding: 2px 10px; text-align: right; border-bottom: 1px solid #333; }
+th:first-child, td:first-child { text-align: left; }
+th { color: #aaa; }
+section { border-top: 2px solid #444; margin-top: 2em; }
+details { margin: 0.3em 0; }
+summary { cursor: pointer; }
+.kill { color: #6c6; }
+.wipe { color: #e66; }
+.heal { color: #6c6; }
+svg text { fill: #aaa; font-size: 11px; }
+";
+
+static COLORS: [&str; 8] = [
+	"#e6194b", "#3cb44b", "#ffe119", "#4363d8", "#f58231", "#911eb4", "#46f0f0", "#f032e6",
+];
+
+const CHART_W: f64 = 800.;
+const CHART_H: f64 = 200.;
+
+// a single offline html page with every encounter, no external resources
+pub fn write_html<W: Write>(w: &mut W, title: &str, encs: &[Encounter]) -> io::Result<()> {
+	writeln!(
+		w,
+		"<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">"
+	)?;
+	writeln!(
+		w,
+		"<title>{}</title>\n<style>{STYLE}</style>\n</head>\n<body>",
+		esc(title)
+	)?;
+	writeln!(w, "<h1>{}</h1>", esc(title))?;
+
+	write_toc(w, encs)?;
+	write_summary(w, encs)?;
+	for (i, e) in encs.iter().enumerate() {
+		write_encounter(w, i, e)?;
+	}
+
+	writeln!(w, "</body>\n</html>")
+}
+
+fn write_toc<W: Write>(w: &mut W, encs: &[Encounter]) -> io::Result<()> {
+	// areas in the order they were first entered
+	let mut areas: Vec<&str> = Vec::new();
+	for e in encs {
+		if !areas.contains(&e.area.as_str()) {
+			areas.push(&e.area);
+		}
+	}
+
+	writeln!(w, "<h2>Contents</h2>")?;
+	for area in areas {
+		writeln!(w, "<h3>{}</h3>\n<ul>", esc(area))?;
+		for (i, e) in encs.iter().enumerate().filter(|(_, e)| e.area == area) {
+			writeln!(
+				w,
+				"<li><a href=\"#enc-{i}\">{} {}</a></li>",
+				e.start.format("%H:%M:%S"),
+				esc(&e.status())
+			)?;
+		}
+		writeln!(w, "</ul>")?;
+	}
+	Ok(())
+}
+
+fn write_summary<W: Write>(w: &mut W, encs: &[Encounter]) -> io::Result<()> {
+	writeln!(w, "<h2>Summary</h2>\n<table>")?;
+	writeln!(
+		w,
+		"<tr><th>Encounter</th><th>Start</th><th>Outcome</th><th>Time</th><th>Players</th>\
+		<th>Deaths</th><th>DPS</th><th>HPS</th><th>DTPS</th></tr>"
+	)?;
+	for (i, e) in encs.iter().enumerate() {
+		let sum = e.summary();
+		let outcome = e.outcome().name();
+		writeln!(
+			w,
+			"<tr><td><a href=\"#enc-{i}\">{}</a></td><td>{}</td><td class=\"{}\">{}</td>\
+			<td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+			esc(&e.name()),
+			e.start.format("%H:%M:%S"),
+			outcome.to_lowercase(),
+			outcome,
+			fmt_duration(sum.elapsed),
+			sum.raid.players,
+			sum.raid.deaths,
+			fmt_num(sum.dps()),
+			fmt_num(sum.hps()),
+			fmt_num(sum.dtps()),
+		)?;
+	}
+	writeln!(w, "</table>")
+}
+
+fn write_encounter<W: Write>(w: &mut W, i: usize, e: &Encounter) -> io::Result<()> {
+	let elapsed = e.elapsed().num_seconds();
+	writeln!(w, "<section id=\"enc-{i}\">")?;
+	writeln!(
+		w,
+		"<h2>{}</h2>\n<p>{} - {} ({}), {}</p>",
+		esc(&e.status()),
+		e.start.format("%H:%M:%S"),
+		e.end.format("%H:%M:%S"),
+		fmt_duration(elapsed),
+		esc(&e.area)
+	)?;
+
+	write_chart(w, e)?;
+
+	let tables = [
+		("Damage", e.dmg_out_rows()),
+		("Healing", e.heals_out_rows()),
+		("Damage taken", e.dmg_in_rows()),
+		("Healing taken", e.heals_in_rows()),
+	];
+	for (name, rows) in tables {
+		write_player_table(w, name, &rows)?;
+	}
+
+	writeln!(w, "<h3>Breakdown</h3>")?;
+	for p in e.players.iter() {
+		writeln!(w, "<details><summary>{}</summary>", esc(&p.id.name))?;
+		let kinds = [
+			("Abilities", &p.spells_out),
+			("Damage by target", &p.dmg_out),
+			("Healing by target", &p.heal_out),
+			("Taken by ability", &p.spells_in),
+		];
+		for (name, v) in kinds {
+			let rows = ActorStats::meter_rows(v, elapsed);
+			if !rows.is_empty() {
+				write_meter_table(w, name, &rows)?;
+			}
+		}
+		writeln!(w, "</details>")?;
+	}
+
+	write_deaths(w, e)?;
+	writeln!(w, "</section>")
+}
+
+fn write_player_table<W: Write>(w: &mut W, name: &str, rows: &[PlayerRow]) -> io::Result<()> {
+	let rows = rows
+		.iter()
+		.filter(|r| r.meter.total > 0)
+		.collect::<Vec<_>>();
+	if rows.is_empty() {
+		return Ok(());
+	}
+	writeln!(w, "<h3>{name}</h3>\n<table>")?;
+	writeln!(
+		w,
+		"<tr><th>Player</th><th>Discipline</th><th>Casts</th><th>Total</th><th>Crit</th>\
+		<th>APM</th><th>Per second</th></tr>"
+	)?;
+	for r in rows {
+		writeln!(
+			w,
+			"<tr><td>{}</td><td>{}</td>{}</tr>",
+			esc(&r.id.name),
+			esc(&r.spec),
+			meter_cells(&r.meter)
+		)?;
+	}
+	writeln!(w, "</table>")
+}
+
+fn write_meter_table<W: Write>(w: &mut W, name: &str, rows: &[MeterRow]) -> io::Result<()> {
+	writeln!(w, "<h4>{name}</h4>\n<table>")?;
+	writeln!(
+		w,
+		"<tr><th>Name</th><th>Casts</th><th>Total</th><th>Crit</th><th>APM</th>\
+		<th>Per second</th></tr>"
+	)?;
+	for r in rows {
+		writeln!(w, "<tr><td>{}</td>{}</tr>", esc(&r.name), meter_cells(r))?;
+	}
+	writeln!(w, "</table>")
+}
+
+fn meter_cells(m: &MeterRow) -> String {
+	format!(
+		"<td>{}</td><td>{}</td><td>{}%</td><td>{}</td><td>{}</td>",
+		m.casts,
+		fmt_num(m.total as f64),
+		fmt_num(m.crit_pct),
+		fmt_num(m.apm),
+		fmt_num(m.xps)
+	)
+}
+
+// one line per player, damage done per second smoothed over a 5 second window
+fn write_chart<W: Write>(w: &mut W, e: &Encounter) -> io::Result<()> {
+	let seconds = e.elapsed().num_seconds();
+	if seconds < 2 {
+		return Ok(());
+	}
+
+	let series = e
+		.players
+		.iter()
+		.filter(|p| p.dmg_total.total > 0 && !p.dmg_curve.is_empty())
+		.map(|p| (&p.id.name, smooth(&p.dps_series(e.start, seconds), 5)))
+		.collect::<Vec<_>>();
+	let max = series
+		.iter()
+		.flat_map(|(_, s)| s.iter())
+		.fold(0f64, |a, b| a.max(*b));
+	if series.is_empty() || max <= 0. {
+		return Ok(());
+	}
+
+	writeln!(
+		w,
+		"<h3>DPS</h3>\n<svg width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">",
+		CHART_W + 160.,
+		CHART_H + 20.,
+		CHART_W + 160.,
+		CHART_H + 20.
+	)?;
+	writeln!(
+		w,
+		"<text x=\"0\" y=\"10\">{}</text><text x=\"0\" y=\"{}\">{}</text>",
+		fmt_num(max),
+		CHART_H + 15.,
+		fmt_duration(seconds)
+	)?;
+
+	let step = CHART_W / seconds as f64;
+	for (i, (name, s)) in series.iter().enumerate() {
+		let color = COLORS[i % COLORS.len()];
+		let points = s
+			.iter()
+			.enumerate()
+			.map(|(x, y)| format!("{:.1},{:.1}", x as f64 * step, CHART_H - y / max * CHART_H))
+			.collect::<Vec<_>>()
+			.join(" ");
+		writeln!(
+			w,
+			"<polyline fill=\"none\" stroke=\"{color}\" stroke-width=\"1.5\" points=\"{points}\"/>"
+		)?;
+		writeln!(
+			w,
+			"<text x=\"{}\" y=\"{}\" style=\"fill: {color}\">{}</text>",
+			CHART_W + 10.,
+			15 + i * 14,
+			esc(name)
+		)?;
+	}
+	writeln!(w, "</svg>")
+}
+
+fn write_deaths<W: Write>(w: &mut W, e: &Encounter) -> io::Result<()> {
+	let dead = e
+		.players
+		.iter()
+		.filter(|p| !p.death_recaps.is_empty())
+		.collect::<Vec<_>>();
+	if dead.is_empty() {
+		return Ok(());
+	}
+
+	writeln!(w, "<h3>Deaths</h3>")?;
+	for p in dead {
+		for d in &p.death_recaps {
+			writeln!(
+				w,
+				"<details><summary>{} died at {}</summary>\n<table>",
+				esc(&p.id.name),
+				d.ts.format("%H:%M:%S%.3f")
+			)?;
+			writeln!(
+				w,
+				"<tr><th>Time</th><th>Source</th><th>Ability</th><th>Amount</th></tr>"
+			)?;
+			for ev in &d.events {
+				writeln!(
+					w,
+					"<tr{}><td>{}</td><td>{}</td><td>{}</td><td>{}{}{}</td></tr>",
+					if ev.heal { " class=\"heal\"" } else { "" },
+					ev.ts.format("%H:%M:%S%.3f"),
+					esc(&ev.source),
+					esc(&ev.ability),
+					if ev.heal { "+" } else { "-" },
+					ev.value,
+					if ev.critical { "*" } else { "" }
+				)?;
+			}
+			writeln!(w, "</table></details>")?;
+		}
+	}
+	Ok(())
+}
+
+fn smooth(s: &[i32], window: usize) -> Vec<f64> {
+	(0..s.len())
+		.map(|i| {
+			let from = i.saturating_sub(window - 1);
+			let v = &s[from..=i];
+			v.iter().sum::<i32>() as f64 / v.len() as f64
+		})
+		.collect()
+}
+
+fn fmt_duration(seconds: i64) -> String {
+	format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn esc(s: &str) -> String {
+	s.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	use crate::{
+		fixtures::BRONTES,
+		parser::{encounter::Encounters, line::Line},
+	};
+
+	#[test]
+	fn html() {
+		let mut encs = Encounters::new();
+		for l in BRONTES.lines() {
+			encs.push(Line::new(l).unwrap(), &|_, _| {});
+		}
+		let encs = encs.finish();
+		let tank = encs[0].players.iter().next().unwrap();
+		assert_eq!(tank.dps_series(encs[0].start, 3), vec![0, 230, 0, 0]);
+
+		let mut out = Vec::new();
+		write_html(&mut out, "Brontes <8m>", &encs).unwrap();
+		let html = String::from_utf8(out).unwrap();
+
+		assert!(html.starts_with("<!DOCTYPE html>"));
+		assert!(html.trim_end().ends_with("</html>"));
+		assert!(html.contains("<title>Brontes &lt;8m&gt;</title>"));
+		assert!(html.contains("<h3>The Dread Fortress</h3>"));
+		assert!(html.contains("<a href=\"#enc-1\">20:01:00 Brontes (8m Veteran) pull 2, kill</a>"));
+		assert!(html.contains("<td class=\"wipe\">wipe</td>"));
+		assert!(html.contains("<section id=\"enc-0\">"));
+		assert!(html.contains("<h2>Brontes (8m Veteran) pull 1, wipe at 77%</h2>"));
+		assert_eq!(html.matches("<svg").count(), 2);
+		assert!(html.contains("<h3>Damage</h3>"));
+		assert!(html.contains("<tr><td>Strike</td><td>1</td><td>1000</td><td>100%</td>"));
+		assert!(!html.contains("NaN") && !html.contains("inf"));
+	}
+
+	#[test]
+	fn helpers() {
+		assert_eq!(
+			esc("<Tank & \"Heals\">"),
+			"&lt;Tank &amp; &quot;Heals&quot;&gt;"
+		);
+		assert_eq!(smooth(&[10, 20, 30], 2), vec![10., 15., 25.]);
+		assert_eq!(fmt_duration(125), "2:05");
+	}
+}
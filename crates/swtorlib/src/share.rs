@@ -0,0 +1,505 @@
+use std::{
+	collections::HashMap,
+	io,
+	sync::{Arc, Mutex},
+};
+
+use chrono::{Duration, NaiveTime};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tokio::{
+	io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+	net::{tcp::OwnedWriteHalf, TcpListener, TcpStream, ToSocketAddrs},
+	sync::{broadcast, mpsc},
+};
+
+use crate::parser::{
+	action::Action,
+	encounter::Encounter,
+	line::Line,
+	merge::merge,
+	utils::{Packet, MAX_PACKET},
+};
+
+// message codes, the packet's req field is the sender's peer id as assigned by the hub
+pub const HELLO: u16 = 1;
+pub const SUMMARY: u16 = 2;
+pub const LINES: u16 = 3;
+
+// summaries whose encounters started this close together are the same fight seen by
+// different peers, their clocks are not in sync
+const SAME_FIGHT_SECS: i64 = 30;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Hello {
+	// the local player's character name
+	pub name: String,
+	pub version: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlayerSummary {
+	pub name: String,
+	pub spec: String,
+	pub role: String,
+	pub dmg_out: i64,
+	pub dmg_in: i64,
+	pub heal_out: i64,
+	pub heal_in: i64,
+	pub deaths: i32,
+}
+
+// one peer's view of an encounter
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Summary {
+	pub start: NaiveTime,
+	pub area: String,
+	pub name: String,
+	pub status: String,
+	pub elapsed: i64,
+	pub players: Vec<PlayerSummary>,
+}
+
+impl Summary {
+	pub fn new(e: &Encounter) -> Self {
+		Self {
+			start: e.start,
+			area: e.area.clone(),
+			name: e.name(),
+			status: e.status(),
+			elapsed: e.elapsed().num_seconds(),
+			players: e
+				.players
+				.iter()
+				.map(|p| PlayerSummary {
+					name: p.id.name.clone(),
+					spec: p.spec_name(),
+					role: p.role().name().to_owned(),
+					dmg_out: p.all_dmg_out().total as i64,
+					dmg_in: p.all_dmg_in().total as i64,
+					heal_out: p.all_heal_out().total as i64,
+					heal_in: p.all_heal_in().total as i64,
+					deaths: p.deaths,
+				})
+				.collect(),
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+	Hello(Hello),
+	Summary(Summary),
+	// the lines of the sender's current encounter since the last batch
+	Lines(Vec<Line>),
+}
+
+impl Message {
+	pub fn code(&self) -> u16 {
+		match self {
+			Self::Hello(_) => HELLO,
+			Self::Summary(_) => SUMMARY,
+			Self::Lines(_) => LINES,
+		}
+	}
+
+	pub fn to_bytes(&self, peer: u16, seq: u32) -> io::Result<Vec<u8>> {
+		let body = match self {
+			Self::Hello(h) => serde_json::to_vec(h),
+			Self::Summary(s) => serde_json::to_vec(s),
+			Self::Lines(l) => serde_json::to_vec(l),
+		}
+		.map_err(invalid)?;
+		Ok(Packet::encode(self.code(), peer, seq, &body))
+	}
+
+	pub fn from_packet(p: &Packet) -> io::Result<Self> {
+		let body = p.body()?;
+		match p.code() {
+			HELLO => Ok(Self::Hello(decode(&body)?)),
+			SUMMARY => Ok(Self::Summary(decode(&body)?)),
+			LINES => Ok(Self::Lines(decode(&body)?)),
+			code => Err(invalid(format!("unknown message code {code}"))),
+		}
+	}
+}
+
+fn decode<T: DeserializeOwned>(body: &[u8]) -> io::Result<T> {
+	serde_json::from_slice(body).map_err(invalid)
+}
+
+fn invalid<E: Into<Box<dyn std::error::Error + Send + Sync>>>(e: E) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+// reads one whole packet, header and body
+pub async fn read_frame<R: AsyncRead + Unpin>(r: &mut R) -> io::Result<Vec<u8>> {
+	let mut buf = vec![0; Packet::SIZE];
+	r.read_exact(&mut buf).await?;
+	let size = u32::from_be_bytes([buf[12], buf[13], buf[14], buf[15]]) as usize;
+	if size > MAX_PACKET {
+		return Err(invalid("packet too large"));
+	}
+	buf.resize(Packet::SIZE + size, 0);
+	r.read_exact(&mut buf[Packet::SIZE..]).await?;
+	Packet::from_bytes(&buf)?;
+	Ok(buf)
+}
+
+// relays every peer's packets to all the other peers, one hub per raid group
+pub struct Hub {
+	listener: TcpListener,
+}
+
+impl Hub {
+	pub async fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+		Ok(Self {
+			listener: TcpListener::bind(addr).await?,
+		})
+	}
+
+	pub fn local_addr(&self) -> io::Result<std::net::SocketAddr> {
+		self.listener.local_addr()
+	}
+
+	pub async fn run(self) -> io::Result<()> {
+		let (tx, _) = broadcast::channel::<(u16, Arc<Vec<u8>>)>(256);
+		// the last hello and summary of every peer, sent to peers joining late
+		let last = Arc::new(Mutex::new(HashMap::<(u16, u16), Arc<Vec<u8>>>::new()));
+		let mut next_id = 0u16;
+
+		loop {
+			let (conn, _) = self.listener.accept().await?;
+			next_id = next_id.wrapping_add(1).max(1);
+			let id = next_id;
+			let (mut r, mut w) = conn.into_split();
+
+			let mut rx = tx.subscribe();
+			let backlog = last.lock().unwrap().values().cloned().collect::<Vec<_>>();
+			tokio::spawn(async move {
+				for f in backlog {
+					w.write_all(&f).await?;
+				}
+				loop {
+					match rx.recv().await {
+						Ok((from, f)) if from != id => w.write_all(&f).await?,
+						Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+						Err(broadcast::error::RecvError::Closed) => return io::Result::Ok(()),
+					}
+				}
+			});
+
+			let tx = tx.clone();
+			let last = last.clone();
+			tokio::spawn(async move {
+				while let Ok(mut f) = read_frame(&mut r).await {
+					// stamp the sender so receivers can tell peers apart
+					f[6..8].copy_from_slice(&id.to_be_bytes());
+					let code = u16::from_be_bytes([f[4], f[5]]);
+					let f = Arc::new(f);
+					if code == HELLO || code == SUMMARY {
+						last.lock().unwrap().insert((id, code), f.clone());
+					}
+					let _ = tx.send((id, f));
+				}
+				last.lock().unwrap().retain(|(peer, _), _| *peer != id);
+			});
+		}
+	}
+}
+
+pub struct Client {
+	rx: mpsc::Receiver<io::Result<(u16, Message)>>,
+	w: OwnedWriteHalf,
+	seq: u32,
+}
+
+impl Client {
+	// connects to a hub and introduces the local player
+	pub async fn connect<A: ToSocketAddrs>(addr: A, hello: Hello) -> io::Result<Self> {
+		let (mut r, w) = TcpStream::connect(addr).await?.into_split();
+		// reading a frame is not cancel safe, so it happens in its own task and recv only
+		// waits on the channel
+		let (tx, rx) = mpsc::channel(64);
+		tokio::spawn(async move {
+			loop {
+				let m = read_message(&mut r).await;
+				let failed = m.is_err();
+				if tx.send(m).await.is_err() || failed {
+					return;
+				}
+			}
+		});
+		let mut c = Self { rx, w, seq: 0 };
+		c.send(&Message::Hello(hello)).await?;
+		Ok(c)
+	}
+
+	pub async fn send(&mut self, m: &Message) -> io::Result<()> {
+		self.seq = self.seq.wrapping_add(1);
+		let buf = m.to_bytes(0, self.seq)?;
+		write_all(&mut self.w, &buf).await
+	}
+
+	// the next message from any other peer, along with its peer id. cancel safe, so it can
+	// be used in select!
+	pub async fn recv(&mut self) -> io::Result<(u16, Message)> {
+		match self.rx.recv().await {
+			Some(m) => m,
+			None => Err(io::ErrorKind::UnexpectedEof.into()),
+		}
+	}
+}
+
+async fn read_message<R: AsyncRead + Unpin>(r: &mut R) -> io::Result<(u16, Message)> {
+	let f = read_frame(r).await?;
+	let (p, _) = Packet::from_bytes(&f)?;
+	Ok((p.req(), Message::from_packet(&p)?))
+}
+
+async fn write_all<W: AsyncWrite + Unpin>(w: &mut W, buf: &[u8]) -> io::Result<()> {
+	w.write_all(buf).await?;
+	w.flush().await
+}
+
+// the latest summary of every peer, merged into one table
+#[derive(Debug, Clone, Default)]
+pub struct Group {
+	names: HashMap<u16, String>,
+	summaries: HashMap<u16, Summary>,
+	// every peer's lines of its current encounter
+	lines: HashMap<u16, Vec<Line>>,
+}
+
+impl Group {
+	pub const LOCAL: u16 = 0;
+
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn update(&mut self, peer: u16, m: Message) {
+		match m {
+			Message::Hello(h) => {
+				self.names.insert(peer, h.name);
+			}
+			Message::Summary(s) => {
+				self.summaries.insert(peer, s);
+			}
+			Message::Lines(v) => {
+				let lines = self.lines.entry(peer).or_default();
+				// a new encounter replaces the old one
+				match v
+					.iter()
+					.rposition(|l| matches!(l.action, Action::EnterCombat))
+				{
+					Some(i) => *lines = v[i..].to_vec(),
+					None => lines.extend(v),
+				}
+			}
+		}
+	}
+
+	pub fn peers(&self) -> impl Iterator<Item = (&u16, &String)> {
+		self.names.iter()
+	}
+
+	// every player seen by any peer in the latest fight, a player's own numbers win over
+	// someone else's view of them, otherwise the view with the most damage done wins
+	pub fn combined(&self) -> Vec<PlayerSummary> {
+		let Some(latest) = self.summaries.values().map(|s| s.start).max() else {
+			return Vec::new();
+		};
+		let same_fight = |s: &&Summary| latest - s.start <= Duration::seconds(SAME_FIGHT_SECS);
+
+		let mut out: Vec<(PlayerSummary, bool)> = Vec::new();
+		for (peer, s) in self.summaries.iter().filter(|(_, s)| same_fight(s)) {
+			let me = self.names.get(peer);
+			for p in &s.players {
+				let own = me == Some(&p.name);
+				match out.iter_mut().find(|(o, _)| o.name == p.name) {
+					Some((o, o_own)) => {
+						if !*o_own && (own || p.dmg_out > o.dmg_out) {
+							*o = p.clone();
+							*o_own = own;
+						}
+					}
+					None => out.push((p.clone(), own)),
+				}
+			}
+		}
+		let mut out = out.into_iter().map(|(p, _)| p).collect::<Vec<_>>();
+		out.sort_by(|a, b| b.dmg_out.cmp(&a.dmg_out).then(a.name.cmp(&b.name)));
+		out
+	}
+
+	// the current encounter merged from the lines of every peer, with the local player's
+	// log as the reference
+	pub fn merged(&self) -> Option<Encounter> {
+		let mut peers = self.lines.keys().copied().collect::<Vec<_>>();
+		peers.sort();
+		let logs = peers
+			.iter()
+			.map(|p| self.lines[p].clone())
+			.filter(|v| !v.is_empty())
+			.collect();
+		merge(logs).pop()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	use crate::fixtures::BRONTES;
+
+	fn start(ts: &str) -> NaiveTime {
+		ts.parse().unwrap()
+	}
+
+	fn summary(at: &str, players: &[(&str, i64)]) -> Summary {
+		Summary {
+			start: start(at),
+			name: "Brontes (8m Veteran)".to_owned(),
+			players: players
+				.iter()
+				.map(|(name, dmg)| PlayerSummary {
+					name: name.to_string(),
+					dmg_out: *dmg,
+					..PlayerSummary::default()
+				})
+				.collect(),
+			..Summary::default()
+		}
+	}
+
+	fn hello(name: &str) -> Hello {
+		Hello {
+			name: name.to_owned(),
+			version: "test".to_owned(),
+		}
+	}
+
+	fn lines(log: &str) -> Vec<Line> {
+		log.lines().map(|l| Line::new(l).unwrap()).collect()
+	}
+
+	#[tokio::test]
+	async fn two_peers() {
+		let hub = Hub::bind("127.0.0.1:0").await.unwrap();
+		let addr = hub.local_addr().unwrap();
+		tokio::spawn(hub.run());
+
+		let mut heals = Client::connect(addr, hello("Heals")).await.unwrap();
+		let mut tank = Client::connect(addr, hello("Tank")).await.unwrap();
+		tank.send(&Message::Summary(summary(
+			"20:01:02",
+			&[("Tank", 1000), ("Heals", 10)],
+		)))
+		.await
+		.unwrap();
+
+		let mut group = Group::new();
+		group.update(Group::LOCAL, Message::Hello(hello("Heals")));
+		group.update(
+			Group::LOCAL,
+			Message::Summary(summary("20:01:00", &[("Heals", 200), ("Tank", 900)])),
+		);
+		for _ in 0..2 {
+			let (peer, m) = heals.recv().await.unwrap();
+			assert_ne!(peer, Group::LOCAL);
+			group.update(peer, m);
+		}
+
+		let combined = group
+			.combined()
+			.into_iter()
+			.map(|p| (p.name, p.dmg_out))
+			.collect::<Vec<_>>();
+		assert_eq!(
+			combined,
+			vec![("Tank".to_owned(), 1000), ("Heals".to_owned(), 200)]
+		);
+
+		let log = lines(BRONTES);
+		heals.send(&Message::Lines(log.clone())).await.unwrap();
+		loop {
+			match tank.recv().await.unwrap() {
+				(_, Message::Hello(h)) => assert_eq!(h.name, "Heals"),
+				(_, Message::Lines(v)) => break assert_eq!(v.len(), log.len()),
+				(_, m) => panic!("{m:?}"),
+			}
+		}
+	}
+
+	#[tokio::test]
+	async fn recv_in_select() {
+		let hub = Hub::bind("127.0.0.1:0").await.unwrap();
+		let addr = hub.local_addr().unwrap();
+		tokio::spawn(hub.run());
+
+		let mut tank = Client::connect(addr, hello("Tank")).await.unwrap();
+		let mut heals = Client::connect(addr, hello("Heals")).await.unwrap();
+		let big = Message::Lines(lines(&BRONTES.repeat(200)));
+		heals.send(&big).await.unwrap();
+
+		// recv is cancelled over and over while the packet is coming in, no bytes are lost
+		let mut got = Vec::new();
+		while got.len() < 2 {
+			tokio::select! {
+				m = tank.recv() => got.push(m.unwrap().1),
+				_ = tokio::task::yield_now() => {}
+			}
+		}
+		assert!(matches!(&got[0], Message::Hello(h) if h.name == "Heals"));
+		assert!(matches!(&got[1], Message::Lines(v) if v.len() == 2000));
+	}
+
+	#[test]
+	fn latest_fight_only() {
+		let mut group = Group::new();
+		group.update(1, Message::Hello(hello("Tank")));
+		group.update(2, Message::Hello(hello("Heals")));
+		// the tank's summary is from the pull before, the healer's clock is 2s ahead
+		group.update(1, Message::Summary(summary("20:00:01", &[("Tank", 230)])));
+		group.update(
+			2,
+			Message::Summary(summary("20:01:02", &[("Heals", 0), ("Dps", 500)])),
+		);
+		group.update(
+			Group::LOCAL,
+			Message::Summary(summary("20:01:00", &[("Dps", 600)])),
+		);
+		let names = group
+			.combined()
+			.into_iter()
+			.map(|p| (p.name, p.dmg_out))
+			.collect::<Vec<_>>();
+		assert_eq!(
+			names,
+			vec![("Dps".to_owned(), 600), ("Heals".to_owned(), 0)]
+		);
+	}
+
+	#[test]
+	fn merged_lines() {
+		let log = lines(BRONTES);
+		let mut group = Group::new();
+		group.update(Group::LOCAL, Message::Lines(log[..6].to_vec()));
+		// only the current encounter is kept
+		group.update(Group::LOCAL, Message::Lines(log[6..8].to_vec()));
+		group.update(Group::LOCAL, Message::Lines(log[8..].to_vec()));
+
+		// the healer sees the kill and heals the tank
+		let heal = Line::new("[20:01:00.500] [@Heals#2|(0,0,0,0)|(100/100)] [@Tank#1|(0,0,0,0)|(90/100)] [Heal {3}] [ApplyEffect {836045448945477}: Heal {836045448945500}] (300)").unwrap();
+		let mut theirs = log[6..].to_vec();
+		theirs.insert(1, heal);
+		group.update(2, Message::Lines(theirs));
+
+		let e = group.merged().unwrap();
+		assert_eq!(e.start, start("20:01:00"));
+		assert_eq!(e.players.len(), 2);
+		let tank = e.players.iter().find(|p| p.id.name == "Tank").unwrap();
+		assert_eq!(tank.all_dmg_out().total, 1000);
+		assert_eq!(tank.all_heal_in().total, 300);
+	}
+}
@@ -0,0 +1,388 @@
+use std::{io, path::Path};
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::parser::{
+	actor_stats::{ActorStats, Meter},
+	bests::{Bests, Key, Record},
+	encounter::{Encounter, Outcome},
+	sorted_vec::SortedVec,
+};
+
+static SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS encounters (
+	id INTEGER PRIMARY KEY,
+	log TEXT NOT NULL,
+	start TEXT NOT NULL,
+	end TEXT NOT NULL,
+	elapsed INTEGER NOT NULL,
+	area TEXT NOT NULL,
+	area_id INTEGER NOT NULL,
+	difficulty TEXT NOT NULL,
+	boss TEXT,
+	pull INTEGER NOT NULL,
+	outcome TEXT NOT NULL,
+	local_player TEXT,
+	UNIQUE (log, start)
+);
+
+CREATE TABLE IF NOT EXISTS players (
+	id INTEGER PRIMARY KEY,
+	encounter_id INTEGER NOT NULL REFERENCES encounters(id) ON DELETE CASCADE,
+	name TEXT NOT NULL,
+	player_id INTEGER NOT NULL,
+	class TEXT NOT NULL,
+	spec TEXT NOT NULL,
+	role TEXT NOT NULL,
+	dmg_out INTEGER NOT NULL,
+	dmg_in INTEGER NOT NULL,
+	heal_out INTEGER NOT NULL,
+	heal_in INTEGER NOT NULL,
+	deaths INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS meters (
+	player_id INTEGER NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+	kind TEXT NOT NULL,
+	name TEXT NOT NULL,
+	meter_id INTEGER NOT NULL,
+	casts INTEGER NOT NULL,
+	total INTEGER NOT NULL,
+	crits INTEGER NOT NULL,
+	crit_total INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS encounters_boss ON encounters (boss, outcome);
+CREATE INDEX IF NOT EXISTS players_name ON players (name);
+";
+
+// an encounter as stored in the database, along with one player's numbers
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Performance {
+	pub encounter_id: i64,
+	pub log: String,
+	pub start: String,
+	pub elapsed: i64,
+	pub area: String,
+	pub difficulty: String,
+	pub boss: Option<String>,
+	pub pull: u32,
+	pub outcome: String,
+
+	pub name: String,
+	pub spec: String,
+	pub role: String,
+	pub dmg_out: i64,
+	pub dmg_in: i64,
+	pub heal_out: i64,
+	pub heal_in: i64,
+	pub deaths: i32,
+}
+
+impl Performance {
+	pub fn dps(&self) -> f64 {
+		self.dmg_out as f64 / self.elapsed.max(1) as f64
+	}
+
+	pub fn hps(&self) -> f64 {
+		self.heal_out as f64 / self.elapsed.max(1) as f64
+	}
+
+	pub fn dtps(&self) -> f64 {
+		self.dmg_in as f64 / self.elapsed.max(1) as f64
+	}
+}
+
+pub struct Storage {
+	conn: Connection,
+}
+
+impl Storage {
+	pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+		Self::init(Connection::open(path).map_err(to_io)?)
+	}
+
+	pub fn open_in_memory() -> io::Result<Self> {
+		Self::init(Connection::open_in_memory().map_err(to_io)?)
+	}
+
+	// <data dir>/swtormeter/encounters.db
+	pub fn default_path() -> Option<String> {
+		let dir = dirs_next::data_dir()?.join("swtormeter");
+		Some(dir.join("encounters.db").display().to_string())
+	}
+
+	fn init(conn: Connection) -> io::Result<Self> {
+		conn.execute_batch("PRAGMA foreign_keys = ON;")
+			.map_err(to_io)?;
+		conn.execute_batch(SCHEMA).map_err(to_io)?;
+		Ok(Self { conn })
+	}
+
+	// saves every encounter not already stored for log, returns how many were added
+	pub fn save_all(&mut self, log: &str, encs: &[Encounter]) -> io::Result<usize> {
+		let tx = self.conn.transaction().map_err(to_io)?;
+		let mut n = 0;
+		for e in encs {
+			if Self::insert(&tx, log, e)? {
+				n += 1;
+			}
+		}
+		tx.commit().map_err(to_io)?;
+		Ok(n)
+	}
+
+	// returns false if the encounter was already stored
+	pub fn save(&mut self, log: &str, e: &Encounter) -> io::Result<bool> {
+		Ok(self.save_all(log, std::slice::from_ref(e))? == 1)
+	}
+
+	fn insert(conn: &Connection, log: &str, e: &Encounter) -> io::Result<bool> {
+		let log = log_name(log);
+		let added = conn
+			.execute(
+				"INSERT OR IGNORE INTO encounters
+				(log, start, end, elapsed, area, area_id, difficulty, boss, pull, outcome, local_player)
+				VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+				params![
+					log,
+					fmt_time(e.start),
+					fmt_time(e.end),
+					e.elapsed().num_seconds(),
+					e.area,
+					e.area_id as i64,
+					e.difficulty.to_string(),
+					e.boss_name(),
+					e.pull,
+					e.outcome().name(),
+					e.local_player.as_ref().map(|p| p.name.clone()),
+				],
+			)
+			.map_err(to_io)?;
+		if added == 0 {
+			return Ok(false);
+		}
+
+		let enc_id = conn.last_insert_rowid();
+		for p in e.players.iter() {
+			conn.execute(
+				"INSERT INTO players
+				(encounter_id, name, player_id, class, spec, role, dmg_out, dmg_in, heal_out, heal_in, deaths)
+				VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+				params![
+					enc_id,
+					p.id.name,
+					p.id.id as i64,
+					p.class.name,
+					p.spec_name(),
+					p.role().name(),
+					p.all_dmg_out().total,
+					p.all_dmg_in().total,
+					p.all_heal_out().total,
+					p.all_heal_in().total,
+					p.deaths,
+				],
+			)
+			.map_err(to_io)?;
+
+			let player_id = conn.last_insert_rowid();
+			Self::insert_meters(conn, player_id, p)?;
+		}
+		Ok(true)
+	}
+
+	fn insert_meters(conn: &Connection, player_id: i64, p: &ActorStats) -> io::Result<()> {
+		let mut stmt = conn
+			.prepare_cached(
+				"INSERT INTO meters (player_id, kind, name, meter_id, casts, total, crits, crit_total)
+				VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+			)
+			.map_err(to_io)?;
+
+		let kinds: [(&str, &SortedVec<Meter>); 6] = [
+			("spells_out", &p.spells_out),
+			("spells_in", &p.spells_in),
+			("dmg_out", &p.dmg_out),
+			("dmg_in", &p.dmg_in),
+			("heal_out", &p.heal_out),
+			("heal_in", &p.heal_in),
+		];
+		for (kind, v) in kinds {
+			for m in v.iter() {
+				stmt.execute(params![
+					player_id,
+					kind,
+					m.id.name,
+					m.id.id as i64,
+					m.casts,
+					m.total,
+					m.crits,
+					m.crit_total,
+				])
+				.map_err(to_io)?;
+			}
+		}
+		Ok(())
+	}
+
+	pub fn has_encounter(&self, log: &str, e: &Encounter) -> io::Result<bool> {
+		self.conn
+			.query_row(
+				"SELECT 1 FROM encounters WHERE log = ?1 AND start = ?2",
+				params![log_name(log), fmt_time(e.start)],
+				|_| Ok(()),
+			)
+			.optional()
+			.map(|r| r.is_some())
+			.map_err(to_io)
+	}
+
+	// every pull of boss by character, oldest first, outcome filters on Kill, Wipe etc
+	pub fn pulls(
+		&self,
+		boss: &str,
+		character: &str,
+		outcome: Option<Outcome>,
+	) -> io::Result<Vec<Performance>> {
+		let mut stmt = self
+			.conn
+			.prepare_cached(
+				"SELECT e.id, e.log, e.start, e.elapsed, e.area, e.difficulty, e.boss, e.pull, e.outcome,
+				p.name, p.spec, p.role, p.dmg_out, p.dmg_in, p.heal_out, p.heal_in, p.deaths
+				FROM encounters e JOIN players p ON p.encounter_id = e.id
+				WHERE e.boss = ?1 COLLATE NOCASE AND p.name = ?2 COLLATE NOCASE
+				AND (?3 IS NULL OR e.outcome = ?3)
+				ORDER BY e.log, e.start",
+			)
+			.map_err(to_io)?;
+
+		let rows = stmt
+			.query_map(params![boss, character, outcome.map(|o| o.name())], |r| {
+				Ok(Performance {
+					encounter_id: r.get(0)?,
+					log: r.get(1)?,
+					start: r.get(2)?,
+					elapsed: r.get(3)?,
+					area: r.get(4)?,
+					difficulty: r.get(5)?,
+					boss: r.get(6)?,
+					pull: r.get(7)?,
+					outcome: r.get(8)?,
+					name: r.get(9)?,
+					spec: r.get(10)?,
+					role: r.get(11)?,
+					dmg_out: r.get(12)?,
+					dmg_in: r.get(13)?,
+					heal_out: r.get(14)?,
+					heal_in: r.get(15)?,
+					deaths: r.get(16)?,
+				})
+			})
+			.map_err(to_io)?;
+		rows.collect::<Result<_, _>>().map_err(to_io)
+	}
+
+	// replays every stored boss pull into bests, oldest first
+	pub fn load_bests(&self, bests: &mut Bests) -> io::Result<()> {
+		let mut stmt = self
+			.conn
+			.prepare_cached(
+				"SELECT e.boss, e.difficulty, p.name, p.spec, e.outcome, e.elapsed, p.dmg_out, p.heal_out
+				FROM encounters e JOIN players p ON p.encounter_id = e.id
+				WHERE e.boss IS NOT NULL
+				ORDER BY e.log, e.start",
+			)
+			.map_err(to_io)?;
+		let mut rows = stmt.query([]).map_err(to_io)?;
+		while let Some(r) = rows.next().map_err(to_io)? {
+			let key = Key {
+				boss: r.get(0).map_err(to_io)?,
+				difficulty: r.get(1).map_err(to_io)?,
+				character: r.get(2).map_err(to_io)?,
+				discipline: r.get(3).map_err(to_io)?,
+			};
+			let outcome: String = r.get(4).map_err(to_io)?;
+			let elapsed = r.get::<_, i64>(5).map_err(to_io)?.max(1) as f64;
+			let rec = Record {
+				dps: r.get::<_, i64>(6).map_err(to_io)? as f64 / elapsed,
+				hps: r.get::<_, i64>(7).map_err(to_io)? as f64 / elapsed,
+			};
+			bests.add_record(key, rec, outcome == Outcome::Kill.name());
+		}
+		Ok(())
+	}
+
+	// "all Brontes kills by Tank"
+	pub fn kills(&self, boss: &str, character: &str) -> io::Result<Vec<Performance>> {
+		self.pulls(boss, character, Some(Outcome::Kill))
+	}
+}
+
+// only the file name is stored so the same log copied elsewhere is not saved twice
+fn log_name(log: &str) -> String {
+	Path::new(log)
+		.file_name()
+		.map_or_else(|| log.to_owned(), |n| n.to_string_lossy().to_string())
+}
+
+fn fmt_time(t: chrono::NaiveTime) -> String {
+	t.format("%H:%M:%S%.3f").to_string()
+}
+
+fn to_io(e: rusqlite::Error) -> io::Error {
+	io::Error::other(e)
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	use crate::{
+		fixtures::BRONTES,
+		parser::{action::Action, encounter::Encounters, line::Line},
+	};
+
+	#[test]
+	fn save_and_query() {
+		// disciplines are stored by their english name whatever the client language is. the
+		// game logs them when zoning in, before any fight
+		let discipline = Line::new("[20:00:00.500] [@Tank#1|(0,0,0,0)|(100/100)] [] [] [DisciplineChanged {836045448953665}: Juggernaut {16141170711935532310}/Unsterblichkeit {2031339142381578}]").unwrap();
+		let mut encs = Encounters::new();
+		for l in BRONTES.lines() {
+			let l = Line::new(l).unwrap();
+			let area = matches!(l.action, Action::AreaEntered { .. });
+			encs.push(l, &|_, _| {});
+			if area {
+				encs.push(discipline.clone(), &|_, _| {});
+			}
+		}
+		let encs = encs.finish();
+		assert!(encs
+			.iter()
+			.all(|e| e.me().unwrap().spec_name() == "Immortal"));
+
+		let mut db = Storage::open_in_memory().unwrap();
+		let log = "/logs/combat_2026-10-18_20_00_00_000000.txt";
+		assert_eq!(db.save_all(log, &encs).unwrap(), 2);
+		assert_eq!(
+			db.save_all("combat_2026-10-18_20_00_00_000000.txt", &encs)
+				.unwrap(),
+			0
+		);
+		assert!(db.has_encounter(log, &encs[1]).unwrap());
+
+		let kills = db.kills("brontes", "Tank").unwrap();
+		assert_eq!(kills.len(), 1);
+		assert_eq!(kills[0].pull, 2);
+		assert_eq!(kills[0].dmg_out, 1000);
+		assert_eq!(kills[0].spec, "Immortal");
+		assert_eq!(db.pulls("Brontes", "Tank", None).unwrap().len(), 2);
+
+		let mut bests = Bests::new();
+		db.load_bests(&mut bests).unwrap();
+		let me = encs[1].me().unwrap();
+		let key = Key::new(&encs[1], me).unwrap();
+		assert_eq!(key.discipline, "Immortal");
+		assert_eq!(bests.best(&key), Some(&Record::new(&encs[1], me)));
+	}
+}
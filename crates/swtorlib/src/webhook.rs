@@ -0,0 +1,264 @@
+use std::{io, path::Path};
+
+use serde::Deserialize;
+
+use crate::parser::{
+	actor_stats::{ActorStats, Meter},
+	config_dir,
+	encounter::{Encounter, Outcome},
+	utils::fmt_num,
+};
+
+// how many players are listed under top dps / hps
+const TOP: usize = 3;
+
+// posts a summary of every boss kill (and wipe, unless kills_only is set) to url.
+// the template is the json body with {{placeholders}}, every value is a json escaped string
+// so placeholders go inside quotes:
+//   discord: { "content": "{{summary}}" }
+//   slack:   { "text": "{{summary}}" }
+// placeholders: name, area, outcome, duration, deaths, dps, hps, top_dps, top_hps, summary
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Webhook {
+	pub url: String,
+	#[serde(default = "default_template")]
+	pub template: String,
+	#[serde(default)]
+	pub kills_only: bool,
+}
+
+fn default_template() -> String {
+	r#"{"content": "{{summary}}"}"#.to_owned()
+}
+
+impl Webhook {
+	pub fn new(url: &str) -> Self {
+		Self {
+			url: url.to_owned(),
+			template: default_template(),
+			kills_only: false,
+		}
+	}
+
+	// url = "https://discord.com/api/webhooks/..."
+	// template = '{"text": "{{summary}}"}'
+	pub fn from_toml(data: &str) -> io::Result<Self> {
+		toml::from_str(data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+	}
+
+	// <config dir>/swtormeter/webhook.toml if it exists
+	pub fn default_path() -> Option<String> {
+		Self::find(&config_dir()?)
+	}
+
+	// webhook.toml in dir
+	pub fn find(dir: &Path) -> Option<String> {
+		let path = dir.join("webhook.toml");
+		path.is_file().then(|| path.display().to_string())
+	}
+
+	pub fn load(path: &str) -> io::Result<Self> {
+		Self::from_toml(&std::fs::read_to_string(path)?)
+	}
+
+	pub fn wants(&self, e: &Encounter) -> bool {
+		match e.outcome() {
+			Outcome::Kill => true,
+			Outcome::Wipe => !self.kills_only,
+			_ => false,
+		}
+	}
+
+	pub fn render(&self, e: &Encounter) -> String {
+		let sum = e.summary();
+		let seconds = sum.elapsed.max(1);
+		let duration = format!("{}:{:02}", sum.elapsed / 60, sum.elapsed % 60);
+		let top_dps = top(e, seconds, |p| p.all_dmg_out());
+		let top_hps = top(e, seconds, |p| p.all_heal_out());
+
+		let mut title = e.name();
+		if e.pull != 0 {
+			title += &format!(" pull {}", e.pull);
+		}
+		let summary = format!(
+			"**{title}**: {} in {duration}\nTop DPS:\n{top_dps}\nTop HPS:\n{top_hps}\nDeaths: {}",
+			e.outcome().name(),
+			sum.raid.deaths,
+		);
+
+		let vars = [
+			("name", e.name()),
+			("area", e.area.clone()),
+			("outcome", e.outcome().name().to_owned()),
+			("duration", duration),
+			("deaths", sum.raid.deaths.to_string()),
+			("dps", fmt_num(sum.dps())),
+			("hps", fmt_num(sum.hps())),
+			("top_dps", top_dps),
+			("top_hps", top_hps),
+			("summary", summary),
+		];
+		vars.iter().fold(self.template.clone(), |out, (k, v)| {
+			out.replace(&format!("{{{{{k}}}}}"), &json_escape(v))
+		})
+	}
+
+	// blocks until the server answered
+	pub fn post(&self, body: &str) -> io::Result<()> {
+		ureq::post(&self.url)
+			.set("Content-Type", "application/json")
+			.send_string(body)
+			.map_err(io::Error::other)?;
+		Ok(())
+	}
+
+	// posts e in the background if it's a kill or wipe, errors are dropped since there is
+	// nowhere to show them while the meter is running
+	pub fn send(&self, e: &Encounter) {
+		if !self.wants(e) {
+			return;
+		}
+		let body = self.render(e);
+		let hook = self.clone();
+		std::thread::spawn(move || hook.post(&body));
+	}
+}
+
+fn top(e: &Encounter, seconds: i64, f: fn(&ActorStats) -> Meter) -> String {
+	let mut v = e
+		.players
+		.iter()
+		.map(|p| (p.id.name.as_str(), f(p).xps(seconds)))
+		.filter(|(_, xps)| *xps > 0.)
+		.collect::<Vec<_>>();
+	if v.is_empty() {
+		return "-".to_owned();
+	}
+	v.sort_by(|a, b| b.1.total_cmp(&a.1));
+	v.iter()
+		.take(TOP)
+		.enumerate()
+		.map(|(i, (name, xps))| format!("{}. {name} {}", i + 1, fmt_num(*xps)))
+		.collect::<Vec<_>>()
+		.join("\n")
+}
+
+// the contents of a json string, without the quotes
+fn json_escape(s: &str) -> String {
+	let js = serde_json::to_string(s).unwrap_or_default();
+	js.trim_matches('"').to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	use crate::{
+		fixtures::BRONTES,
+		parser::{
+			encounter::Encounters,
+			line::Line,
+			reader::{CaughtUp, Reader, Start},
+		},
+	};
+	use std::{
+		io::{BufRead, BufReader, Read, Write},
+		net::TcpListener,
+		time::Duration,
+	};
+
+	// accepts one request and returns its body
+	fn stub(listener: TcpListener) -> String {
+		let (conn, _) = listener.accept().unwrap();
+		let mut r = BufReader::new(conn);
+		let mut len = 0;
+		loop {
+			let mut h = String::new();
+			r.read_line(&mut h).unwrap();
+			if let Some(v) = h.to_ascii_lowercase().strip_prefix("content-length:") {
+				len = v.trim().parse().unwrap();
+			}
+			if h.trim().is_empty() {
+				break;
+			}
+		}
+		let mut body = vec![0; len];
+		r.read_exact(&mut body).unwrap();
+		r.get_mut()
+			.write_all(b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n")
+			.unwrap();
+		String::from_utf8(body).unwrap()
+	}
+
+	#[test]
+	fn post_on_kill() {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let mut hook = Webhook::new(&format!("http://{}/hook", listener.local_addr().unwrap()));
+		hook.kills_only = true;
+		hook.template =
+			r#"{"text": "{{summary}}", "outcome": "{{outcome}}", "dps": "{{dps}}"}"#.to_owned();
+		let server = std::thread::spawn(move || stub(listener));
+
+		let mut encs = Encounters::new();
+		encs.set_webhook(Some(hook));
+		let caught_up = CaughtUp::default();
+		caught_up.reached(0);
+		encs.set_caught_up(caught_up);
+		for l in BRONTES.lines() {
+			encs.push(Line::new(l).unwrap(), &|_, _| {});
+		}
+
+		let body: serde_json::Value = serde_json::from_str(&server.join().unwrap()).unwrap();
+		assert_eq!(body["outcome"], "kill");
+		assert_eq!(body["dps"], "500");
+		assert_eq!(
+			body["text"],
+			"**Brontes (8m Veteran) pull 2**: kill in 0:02\nTop DPS:\n1. Tank 500\nTop HPS:\n-\nDeaths: 0"
+		);
+	}
+
+	// the kill already in the log is not posted when it's read again, a kill logged while
+	// following it is
+	#[tokio::test]
+	async fn live_only() {
+		let dir = std::env::temp_dir().join(format!("swtormeter-webhook-{}", std::process::id()));
+		std::fs::create_dir_all(&dir).unwrap();
+		let log = dir.join("combat_2026-10-19_20_00_00_000000.txt");
+		std::fs::write(&log, BRONTES).unwrap();
+
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let mut hook = Webhook::new(&format!("http://{}/hook", listener.local_addr().unwrap()));
+		hook.template = r#"{"text": "{{summary}}"}"#.to_owned();
+		let server = std::thread::spawn(move || stub(listener));
+
+		let (mut rx, caught_up) = Reader::follow_dir(dir.to_str().unwrap(), Start::Beginning)
+			.await
+			.unwrap();
+		let mut encs = Encounters::new();
+		encs.set_webhook(Some(hook));
+		encs.set_caught_up(caught_up.clone());
+		let parser = tokio::spawn(async move { encs.process(&mut rx, |_, _| {}).await });
+		while !caught_up.done() {
+			tokio::time::sleep(Duration::from_millis(50)).await;
+		}
+
+		let pull: String = BRONTES
+			.lines()
+			.skip(6)
+			.map(|l| l.replacen("[20:01:", "[20:02:", 1) + "\n")
+			.collect();
+		let mut f = std::fs::OpenOptions::new().append(true).open(&log).unwrap();
+		f.write_all(pull.as_bytes()).unwrap();
+
+		let body = tokio::task::spawn_blocking(move || server.join().unwrap())
+			.await
+			.unwrap();
+		parser.abort();
+		std::fs::remove_dir_all(&dir).unwrap();
+		let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+		assert!(body["text"]
+			.as_str()
+			.unwrap()
+			.starts_with("**Brontes (8m Veteran) pull 3**: kill"));
+	}
+}
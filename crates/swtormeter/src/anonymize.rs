@@ -0,0 +1,8 @@
+use std::error::Error;
+
+use swtorlib::anonymize::anonymize_file;
+
+pub fn run(input: &str, output: &str) -> Result<(), Box<dyn Error>> {
+	anonymize_file(input, output)?;
+	Ok(())
+}
@@ -0,0 +1,186 @@
+use std::path::PathBuf;
+
+use chrono::NaiveTime;
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use swtorlib::parser::reader::Start;
+
+#[derive(Debug, Parser)]
+#[command(
+	name = "swtormeter",
+	version,
+	about = "Combat log meter for Star Wars: The Old Republic"
+)]
+pub struct Cli {
+	/// Directory with the combat logs [default: $LOGS_PATH or the game's log directory]
+	#[arg(long, global = true, value_name = "DIR")]
+	pub logs_dir: Option<String>,
+
+	/// Directory with config.toml, alerts.toml, phases.toml and webhook.toml [default: <config dir>/swtormeter]
+	#[arg(long, global = true, value_name = "DIR")]
+	pub config_dir: Option<PathBuf>,
+
+	#[command(subcommand)]
+	pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+	/// Live meter following the newest log, the default
+	Live {
+		/// Follow this log instead of the newest one
+		#[arg(long, value_name = "LOG")]
+		file: Option<String>,
+
+		/// Where to start reading the first log
+		#[arg(long, value_enum, default_value_t = StartAt::Beginning)]
+		start: StartAt,
+	},
+
+	/// Play a finished log back through the meter
+	Replay {
+		log: String,
+
+		/// Playback speed, 0 shows the whole log at once
+		#[arg(long, default_value_t = 1.)]
+		speed: f64,
+
+		/// Skip ahead to this time of the log, HH:MM:SS
+		#[arg(long, value_name = "TIME")]
+		start: Option<NaiveTime>,
+	},
+
+	/// Write an HTML report of a log
+	Report {
+		log: String,
+
+		/// Output file [default: stdout]
+		#[arg(short, long)]
+		output: Option<String>,
+	},
+
+	/// Export a log's encounters
+	#[command(group(ArgGroup::new("format").required(true).args(["json", "csv", "sqlite"])))]
+	Export {
+		log: String,
+
+		/// JSON to a file or stdout
+		#[arg(long)]
+		json: bool,
+
+		/// <log name>_players.csv and <log name>_breakdown.csv in a directory
+		#[arg(long)]
+		csv: bool,
+
+		/// Append to a SQLite database [default: <data dir>/swtormeter/encounters.db]
+		#[arg(long)]
+		sqlite: bool,
+
+		#[arg(short, long)]
+		output: Option<String>,
+	},
+
+	/// Split a log into one file per encounter
+	Split {
+		log: String,
+
+		/// Output directory
+		#[arg(short, long, default_value = ".")]
+		output: String,
+	},
+
+	/// Print a summary of every encounter in a log
+	Stats { log: String },
+
+	/// Replace player names in a log with pseudonyms
+	Anonymize { input: String, output: String },
+
+	/// Serve the current encounter over HTTP for overlays
+	Serve {
+		#[arg(short, long, default_value_t = 8642)]
+		port: u16,
+	},
+
+	/// Run the relay raid members share their meters through
+	Hub {
+		/// The address to listen on, 0.0.0.0 lets other machines connect
+		#[arg(short, long, default_value = "127.0.0.1")]
+		bind: String,
+		#[arg(short, long, default_value_t = 8643)]
+		port: u16,
+	},
+
+	/// Share the meter with a raid group through a hub
+	Share {
+		/// The hub's host:port
+		addr: String,
+	},
+}
+
+impl Default for Command {
+	fn default() -> Self {
+		Self::Live {
+			file: None,
+			start: StartAt::Beginning,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StartAt {
+	Beginning,
+	/// Only lines written from now on
+	End,
+}
+
+impl From<StartAt> for Start {
+	fn from(s: StartAt) -> Self {
+		match s {
+			StartAt::Beginning => Start::Beginning,
+			StartAt::End => Start::End,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	#[test]
+	fn parse_args() {
+		let cli = Cli::parse_from(["swtormeter"]);
+		assert!(cli.command.is_none());
+
+		let cli = Cli::parse_from([
+			"swtormeter",
+			"replay",
+			"combat.txt",
+			"--speed",
+			"4",
+			"--start",
+			"20:15:00",
+			"--logs-dir",
+			"/logs",
+		]);
+		assert_eq!(cli.logs_dir.as_deref(), Some("/logs"));
+		match cli.command {
+			Some(Command::Replay { log, speed, start }) => {
+				assert_eq!(log, "combat.txt");
+				assert_eq!(speed, 4.);
+				assert_eq!(start, NaiveTime::from_hms_opt(20, 15, 0));
+			}
+			c => panic!("{c:?}"),
+		}
+
+		let cli = Cli::parse_from(["swtormeter", "live", "--start", "end"]);
+		assert!(matches!(
+			cli.command,
+			Some(Command::Live {
+				start: StartAt::End,
+				..
+			})
+		));
+
+		assert!(Cli::try_parse_from(["swtormeter", "export", "combat.txt"]).is_err());
+		assert!(Cli::try_parse_from(["swtormeter", "export", "a.txt", "--json", "--csv"]).is_err());
+	}
+}
@@ -0,0 +1,301 @@
+use std::{
+	io,
+	path::{Path, PathBuf},
+};
+
+use crossterm::event::KeyCode;
+use ratatui::{layout::Constraint, style::Color};
+use serde::{de::Error as _, Deserialize, Deserializer};
+use swtorlib::parser::encounter::EncounterOptions;
+
+// config.toml in the config dir, every setting is optional:
+//   logs_dir = "/games/swtor/CombatLogs"
+//   refresh_ms = 250
+//
+//   [encounters]
+//   merge_companions = true
+//   idle_timeout = 30
+//   bosses_only = true
+//
+//   [columns]
+//   player = [33, 8, 10, 8, 7, 10, 10, 10]
+//
+//   [keys]
+//   quit = "q"
+//   tables = ["1", "2", "3", "4"]
+//
+//   [colors]
+//   header = "blue"
+//   me = "#00ff00"
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+	// after --logs-dir, before LOGS_PATH and the game's directory
+	pub logs_dir: Option<String>,
+	// how often the screen is redrawn when no key is pressed
+	pub refresh_ms: u64,
+	pub encounters: EncounterOptions,
+	pub columns: Columns,
+	pub keys: Keys,
+	pub colors: Colors,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self {
+			logs_dir: None,
+			refresh_ms: 250,
+			encounters: EncounterOptions::default(),
+			columns: Columns::default(),
+			keys: Keys::default(),
+			colors: Colors::default(),
+		}
+	}
+}
+
+impl Config {
+	pub fn path(dir: &Path) -> PathBuf {
+		dir.join("config.toml")
+	}
+
+	pub fn from_toml(data: &str) -> io::Result<Self> {
+		let c: Self =
+			toml::from_str(data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+		c.columns.check()?;
+		// 0 would redraw the screen as fast as it can
+		if c.refresh_ms == 0 {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidData,
+				"refresh_ms needs to be at least 1",
+			));
+		}
+		Ok(c)
+	}
+
+	// a missing file is the defaults
+	pub fn load(path: &Path) -> io::Result<Self> {
+		match std::fs::read_to_string(path) {
+			Ok(data) => Self::from_toml(&data),
+			Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+			Err(e) => Err(e),
+		}
+	}
+}
+
+// percentages of the table width, one per column
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Columns {
+	pub player: Vec<u16>,
+	pub xps: Vec<u16>,
+	pub logs: Vec<u16>,
+}
+
+impl Default for Columns {
+	fn default() -> Self {
+		Self {
+			player: vec![33, 8, 10, 8, 7, 10, 10, 10],
+			xps: vec![45, 10, 10, 10, 7, 10],
+			logs: vec![18, 8, 22, 42, 10],
+		}
+	}
+}
+
+impl Columns {
+	fn check(&self) -> io::Result<()> {
+		let def = Self::default();
+		for (name, v, n) in [
+			("player", &self.player, def.player.len()),
+			("xps", &self.xps, def.xps.len()),
+			("logs", &self.logs, def.logs.len()),
+		] {
+			if v.len() != n {
+				return Err(io::Error::new(
+					io::ErrorKind::InvalidData,
+					format!("columns.{name} needs {n} widths, not {}", v.len()),
+				));
+			}
+		}
+		Ok(())
+	}
+}
+
+pub fn widths(pcts: &[u16]) -> Vec<Constraint> {
+	pcts.iter().map(|p| Constraint::Percentage(*p)).collect()
+}
+
+// the arrow keys, enter and esc in the log picker always work as well
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Keys {
+	pub quit: Key,
+	pub up: Key,
+	pub down: Key,
+	// clears the selected player
+	pub deselect: Key,
+	// opens and closes the log picker
+	pub logs: Key,
+	// healing, damage, dps only, ...
+	pub tables: Vec<Key>,
+}
+
+impl Default for Keys {
+	fn default() -> Self {
+		Self {
+			quit: Key(KeyCode::Char('q')),
+			up: Key(KeyCode::Char('k')),
+			down: Key(KeyCode::Char('j')),
+			deselect: Key(KeyCode::Esc),
+			logs: Key(KeyCode::Char('o')),
+			tables: ('1'..='4').map(|c| Key(KeyCode::Char(c))).collect(),
+		}
+	}
+}
+
+// a single character or the name of a key: "enter", "esc", "up", "f1", ...
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Key(pub KeyCode);
+
+impl TryFrom<String> for Key {
+	type Error = String;
+
+	fn try_from(s: String) -> Result<Self, Self::Error> {
+		let mut chars = s.chars();
+		if let (Some(c), None) = (chars.next(), chars.next()) {
+			return Ok(Self(KeyCode::Char(c)));
+		}
+		let code = match s.to_lowercase().as_str() {
+			"enter" => KeyCode::Enter,
+			"esc" => KeyCode::Esc,
+			"tab" => KeyCode::Tab,
+			"backspace" => KeyCode::Backspace,
+			"space" => KeyCode::Char(' '),
+			"up" => KeyCode::Up,
+			"down" => KeyCode::Down,
+			"left" => KeyCode::Left,
+			"right" => KeyCode::Right,
+			"home" => KeyCode::Home,
+			"end" => KeyCode::End,
+			"pageup" => KeyCode::PageUp,
+			"pagedown" => KeyCode::PageDown,
+			k => match k.strip_prefix('f').and_then(|n| n.parse().ok()) {
+				Some(n) => KeyCode::F(n),
+				None => return Err(format!("unknown key {s:?}")),
+			},
+		};
+		Ok(Self(code))
+	}
+}
+
+impl PartialEq<KeyCode> for Key {
+	fn eq(&self, code: &KeyCode) -> bool {
+		self.0 == *code
+	}
+}
+
+// names like "blue" or "light green", or "#rrggbb"
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Colors {
+	#[serde(deserialize_with = "color")]
+	pub text: Color,
+	// table headers
+	#[serde(deserialize_with = "color")]
+	pub header: Color,
+	#[serde(deserialize_with = "color")]
+	pub header_text: Color,
+	// the local player's row
+	#[serde(deserialize_with = "color")]
+	pub me: Color,
+	// the border of the table the keys move in
+	#[serde(deserialize_with = "color")]
+	pub active: Color,
+	#[serde(deserialize_with = "color")]
+	pub timers: Color,
+	#[serde(deserialize_with = "color")]
+	pub error: Color,
+}
+
+impl Default for Colors {
+	fn default() -> Self {
+		Self {
+			text: Color::Gray,
+			header: Color::Blue,
+			header_text: Color::Black,
+			me: Color::Green,
+			active: Color::Green,
+			timers: Color::Yellow,
+			error: Color::Red,
+		}
+	}
+}
+
+fn color<'de, D: Deserializer<'de>>(d: D) -> Result<Color, D::Error> {
+	let s = String::deserialize(d)?;
+	s.parse()
+		.map_err(|_| D::Error::custom(format!("unknown color {s:?}")))
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+
+	#[test]
+	fn parse_config() {
+		assert_eq!(Config::from_toml("").unwrap(), Config::default());
+
+		let c = Config::from_toml(
+			r##"
+logs_dir = "/logs"
+refresh_ms = 100
+
+[encounters]
+idle_timeout = 30
+
+[columns]
+xps = [40, 10, 10, 10, 10, 20]
+
+[keys]
+quit = "esc"
+down = "down"
+tables = ["h", "d", "F5"]
+
+[colors]
+me = "#00ff00"
+header = "light blue"
+"##,
+		)
+		.unwrap();
+		assert_eq!(c.logs_dir.as_deref(), Some("/logs"));
+		assert_eq!(c.refresh_ms, 100);
+		assert_eq!(c.encounters.idle_timeout, Some(30));
+		assert!(!c.encounters.merge_companions);
+		assert_eq!(c.columns.xps[0], 40);
+		assert_eq!(c.columns.player, Columns::default().player);
+		assert_eq!(c.keys.quit, KeyCode::Esc);
+		assert_eq!(c.keys.up, KeyCode::Char('k'));
+		assert_eq!(c.keys.down, KeyCode::Down);
+		assert_eq!(c.keys.tables[2], KeyCode::F(5));
+		assert_eq!(c.colors.me, Color::Rgb(0, 255, 0));
+		assert_eq!(c.colors.header, Color::LightBlue);
+		assert_eq!(c.colors.text, Color::Gray);
+
+		for bad in [
+			"[columns]\nlogs = [50, 50]",
+			"[keys]\nquit = \"hyper\"",
+			"[colors]\nme = \"mauve\"",
+			"refresh = 100",
+			"refresh_ms = 0",
+		] {
+			assert!(Config::from_toml(bad).is_err(), "{bad}");
+		}
+
+		let missing = std::env::temp_dir().join("swtormeter-no-such-dir");
+		assert_eq!(
+			Config::load(&Config::path(&missing)).unwrap(),
+			Config::default()
+		);
+	}
+}
@@ -0,0 +1,80 @@
+use std::{
+	error::Error,
+	fs::{self, File},
+	io::{self, BufWriter, Write},
+	path::Path,
+};
+
+use swtorlib::{csv, parser::parse_file, storage::Storage};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+	Json,
+	Csv,
+	Sqlite,
+}
+
+impl Format {
+	// from the --json, --csv and --sqlite flags, exactly one of which is set
+	pub fn new(json: bool, csv: bool) -> Self {
+		if json {
+			Self::Json
+		} else if csv {
+			Self::Csv
+		} else {
+			Self::Sqlite
+		}
+	}
+}
+
+pub fn run(log: &str, format: Format, out: Option<&str>) -> Result<(), Box<dyn Error>> {
+	match format {
+		Format::Json => write_json(log, out),
+		Format::Csv => write_csv(log, out.unwrap_or(".")),
+		Format::Sqlite => write_sqlite(log, out.map(str::to_owned).or_else(Storage::default_path)),
+	}
+}
+
+fn write_json(log: &str, out: Option<&str>) -> Result<(), Box<dyn Error>> {
+	let w: Box<dyn Write> = match out {
+		Some(p) => Box::new(File::create(p)?),
+		None => Box::new(io::stdout()),
+	};
+	let encs = parse_file(log)?;
+	let mut w = BufWriter::new(w);
+	serde_json::to_writer_pretty(&mut w, &encs)?;
+	writeln!(w)?;
+	Ok(())
+}
+
+// <dir>/<log name>_players.csv and <dir>/<log name>_breakdown.csv
+fn write_csv(log: &str, dir: &str) -> Result<(), Box<dyn Error>> {
+	let encs = parse_file(log)?;
+	let stem = Path::new(log)
+		.file_stem()
+		.map(|s| s.to_string_lossy().to_string())
+		.unwrap_or_else(|| "combat".to_owned());
+	let dir = Path::new(dir);
+	fs::create_dir_all(dir)?;
+
+	let mut w = BufWriter::new(File::create(dir.join(format!("{stem}_players.csv")))?);
+	csv::write_players(&mut w, &encs)?;
+	w.flush()?;
+
+	let mut w = BufWriter::new(File::create(dir.join(format!("{stem}_breakdown.csv")))?);
+	csv::write_breakdown(&mut w, &encs)?;
+	w.flush()?;
+	Ok(())
+}
+
+// appends the log's encounters to the database, encounters already stored are skipped
+fn write_sqlite(log: &str, db: Option<String>) -> Result<(), Box<dyn Error>> {
+	let db = db.ok_or("no data directory, use -o <db>")?;
+	if let Some(dir) = Path::new(&db).parent() {
+		fs::create_dir_all(dir)?;
+	}
+	let encs = parse_file(log)?;
+	let n = Storage::open(&db)?.save_all(log, &encs)?;
+	eprintln!("{db}: saved {n} of {} encounters", encs.len());
+	Ok(())
+}
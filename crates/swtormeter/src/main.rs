@@ -1,12 +1,17 @@
 use std::{
 	cmp::Ordering,
+	collections::HashSet,
 	error::Error,
 	io,
+	path::{Path, PathBuf},
 	sync::{Arc, Mutex},
-	time::Duration,
+	time::{Duration, SystemTime},
 };
 
 use chrono::NaiveTime;
+use clap::Parser;
+use cli::{Cli, Command, StartAt};
+use config::{widths, Colors, Config};
 use crossterm::{
 	event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
 	execute,
@@ -14,27 +19,96 @@ use crossterm::{
 };
 use ratatui::{prelude::*, widgets::*};
 use swtorlib::{
-	parse,
-	parser::{encounter::Encounter, logs_path, utils::fmt_num},
+	alerts::{AlertEngine, Rules},
+	parse_with,
+	parser::{
+		bests::Bests,
+		catalog::{Catalog, LogInfo},
+		config_dir,
+		encounter::{Encounter, Encounters},
+		logs_path, parse_log,
+		phase::PhaseDefs,
+		reader::{Reader, Start},
+		role::Role,
+		utils::fmt_num,
+	},
+	storage::Storage,
+	webhook::Webhook,
 };
 
+mod anonymize;
+mod cli;
+mod config;
+mod export;
+mod report;
+mod serve;
+mod share;
+mod split;
+mod stats;
+
+static TABLE_NAMES: [&str; 3] = ["Healing", "Damage", "DPS only"];
+
 static XPS_HEADER: [&str; 6] = ["name", "# casts", "total", "crit %", "apm", "xps"];
 
-static XPS_COLUMN_WIDTHS: [Constraint; 6] = [
-	Constraint::Percentage(45),
-	Constraint::Percentage(10),
-	Constraint::Percentage(10),
-	Constraint::Percentage(10),
-	Constraint::Percentage(7),
-	Constraint::Percentage(10),
+static PLAYER_HEADER: [&str; 8] = [
+	"name", "# casts", "total", "crit %", "apm", "xps", "vs. PB", "vs. prev",
 ];
 
+static LOG_HEADER: [&str; 5] = ["date", "size", "characters", "areas", "encounters"];
+
 #[derive(Default)]
 struct App {
 	states: Vec<TableState>,
 	selected: usize,
+	// the local player's row was selected once for this table
+	auto_selected: [bool; 4],
 	npcs: Arc<Mutex<String>>,
 	curr: Arc<Mutex<Encounter>>,
+	alerts: Arc<Mutex<AlertEngine>>,
+	bests: Arc<Mutex<Bests>>,
+	// starts of the open log's encounters already in bests, for when it's parsed again
+	fed: Arc<Mutex<HashSet<NaiveTime>>>,
+
+	dir: String,
+	// a copy is used for every log opened
+	encs: Encounters,
+	// only set on the copy that follows the newest log
+	webhook: Option<Webhook>,
+	parser: Option<tokio::task::JoinHandle<()>>,
+	// why the open log couldn't be read
+	parse_error: Arc<Mutex<Option<String>>>,
+	// the log picker, when it's open
+	logs: Option<(Vec<LogInfo>, TableState)>,
+	// the picker's logs while the logs dir is scanned in the background
+	scan: Option<Scan>,
+	logs_error: Option<String>,
+	source: Source,
+	start: Start,
+
+	config: Config,
+	// config.toml is loaded again whenever it changes
+	config_path: Option<PathBuf>,
+	config_modified: Option<SystemTime>,
+	config_error: Option<String>,
+	// --logs-dir was given, the config's logs_dir is ignored
+	fixed_dir: bool,
+}
+
+// set once the log picker's scan is done
+type Scan = Arc<Mutex<Option<io::Result<Vec<LogInfo>>>>>;
+
+// what the meter is showing
+#[derive(Debug, Clone, Default, PartialEq)]
+enum Source {
+	// follows the newest log in the logs dir
+	#[default]
+	Newest,
+	File(String),
+	Replay {
+		path: String,
+		speed: f64,
+		from: Option<NaiveTime>,
+	},
 }
 
 impl App {
@@ -51,6 +125,187 @@ impl App {
 			..Self::default()
 		}
 	}
+
+	// starts parsing source, replacing whatever was open
+	fn open(&mut self, source: Source, start: Start) {
+		if let Some(h) = self.parser.take() {
+			h.abort();
+		}
+		*self.curr.lock().unwrap() = Encounter::default();
+		*self.parse_error.lock().unwrap() = None;
+		self.npcs.lock().unwrap().clear();
+		self.auto_selected = Default::default();
+		if source != self.source {
+			self.fed.lock().unwrap().clear();
+		}
+		self.source = source.clone();
+		self.start = start;
+
+		let npcs = self.npcs.clone();
+		let curr = self.curr.clone();
+		let alerts = self.alerts.clone();
+		let bests = self.bests.clone();
+		let fed = self.fed.clone();
+		let parse_error = self.parse_error.clone();
+		let dir = self.dir.clone();
+		let mut encs = self.encs.clone();
+		if source == Source::Newest {
+			encs.set_webhook(self.webhook.clone());
+		}
+		self.parser = Some(tokio::spawn(async move {
+			let last = Arc::new(Mutex::new(NaiveTime::default()));
+			let process = |enc: &Encounter, l: &swtorlib::parser::line::Line| {
+				{
+					let mut last = last.lock().unwrap();
+					let mut alerts = alerts.lock().unwrap();
+					if enc.start.cmp(&last) != Ordering::Equal {
+						*last = enc.start;
+						alerts.reset();
+					}
+					alerts.local_player = enc.local_player.clone();
+					alerts.process(l);
+				}
+				{
+					let mut curr = curr.lock().unwrap();
+					if curr.start != enc.start
+						&& !curr.players.is_empty()
+						&& fed.lock().unwrap().insert(curr.start)
+					{
+						bests.lock().unwrap().add(&curr);
+					}
+					*curr = enc.clone();
+				}
+
+				{
+					let mut npcs = npcs.lock().unwrap();
+					*npcs = enc
+						.npc_by_health(true)
+						.into_iter()
+						.map(|(id, v)| format!("{} ({})", id, fmt_num(v as f64)))
+						.collect::<Vec<String>>()
+						.join(", ");
+					if enc.is_boss() {
+						*npcs = format!("** {}", *npcs);
+					}
+				}
+			};
+			let res = match &source {
+				Source::Newest => parse_with(&dir, encs, start, process).await,
+				Source::File(path) => parse_log(path, encs, start, process).await,
+				Source::Replay { path, speed, from } => {
+					match Reader::replay_file(path, *speed, *from).await {
+						Ok(mut rx) => {
+							let mut encs = encs;
+							encs.process(&mut rx, process).await;
+							Ok(())
+						}
+						Err(e) => Err(e),
+					}
+				}
+			};
+			if let Err(e) = res {
+				let what = match &source {
+					Source::Newest => dir,
+					Source::File(path) | Source::Replay { path, .. } => path.clone(),
+				};
+				*parse_error.lock().unwrap() = Some(format!("{what}: {e}"));
+			}
+		}));
+	}
+
+	// lists the logs in dir once they're scanned, the first row follows the newest log
+	fn show_logs(&mut self) {
+		let scan: Scan = Arc::default();
+		let found = scan.clone();
+		let dir = self.dir.clone();
+		tokio::task::spawn_blocking(move || {
+			*found.lock().unwrap() = Some(Catalog::open(&dir).map(|c| c.logs));
+		});
+		self.scan = Some(scan);
+		self.logs_error = None;
+		let mut st = TableState::default();
+		st.select(Some(0));
+		self.logs = Some((vec![], st));
+	}
+
+	// moves the scanned logs into the picker, if it's still open
+	fn scanned(&mut self) {
+		let Some(res) = self.scan.as_ref().and_then(|s| s.lock().unwrap().take()) else {
+			return;
+		};
+		self.scan = None;
+		let Some((logs, st)) = &mut self.logs else {
+			return;
+		};
+		match res {
+			Ok(found) => {
+				if let Source::File(p) = &self.source {
+					let idx = found.iter().position(|l| &l.path == p).map_or(0, |i| i + 1);
+					st.select(Some(idx));
+				}
+				*logs = found;
+			}
+			Err(e) => self.logs_error = Some(format!("{}: {e}", self.dir)),
+		}
+	}
+
+	fn reload_config(&mut self) {
+		let Some(path) = &self.config_path else {
+			return;
+		};
+		let modified = path.metadata().and_then(|m| m.modified()).ok();
+		if modified == self.config_modified {
+			return;
+		}
+		self.config_modified = modified;
+		match Config::load(path) {
+			Ok(c) => {
+				self.config_error = None;
+				self.apply_config(c);
+			}
+			Err(e) => self.config_error = Some(format!("{}: {e}", path.display())),
+		}
+	}
+
+	// new encounter options or logs dir only apply to what was already parsed once the log
+	// is parsed again, so it is, from where it was started before
+	fn apply_config(&mut self, c: Config) {
+		let mut reopen = c.encounters != self.config.encounters;
+		self.encs.set_options(c.encounters.clone());
+		match &c.logs_dir {
+			Some(dir) if !self.fixed_dir && *dir != self.dir => {
+				self.dir = dir.clone();
+				reopen |= self.source == Source::Newest;
+			}
+			_ => {}
+		}
+		self.config = c;
+		if reopen {
+			self.open(self.source.clone(), self.start);
+		}
+	}
+
+	fn pick_log(&mut self) {
+		if let Some((logs, st)) = self.logs.take() {
+			let source = match st.selected() {
+				Some(i) if i > 0 => logs.get(i - 1).map(|l| Source::File(l.path.clone())),
+				_ => None,
+			};
+			self.open(source.unwrap_or_default(), Start::Beginning);
+		}
+	}
+
+	fn move_log(&mut self, down: bool) {
+		if let Some((logs, st)) = &mut self.logs {
+			let rows = logs.len() + 1;
+			let i = st.selected().unwrap_or(0);
+			st.select(Some(if down {
+				(i + 1) % rows
+			} else {
+				(i + rows - 1) % rows
+			}));
+		}
+	}
 	pub fn next(&mut self) {
 		let st = &mut self.states[self.selected];
 		let i = match st.selected() {
@@ -86,6 +341,78 @@ impl App {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
+	let cli = Cli::parse();
+	let config = cli.config_dir.clone().or_else(config_dir);
+	let config_path = config.as_deref().map(Config::path);
+	let settings = match &config_path {
+		Some(path) => Config::load(path)?,
+		None => Config::default(),
+	};
+	let fixed_dir = cli.logs_dir.is_some();
+	let dir = cli
+		.logs_dir
+		.clone()
+		.or_else(|| settings.logs_dir.clone())
+		.or_else(logs_path)
+		.ok_or("no combat logs directory found, use --logs-dir or set LOGS_PATH");
+
+	let (source, start) = match cli.command.unwrap_or_default() {
+		Command::Live { file, start } => (file.map_or(Source::Newest, Source::File), start),
+		Command::Replay { log, speed, start } => {
+			let source = Source::Replay {
+				path: log,
+				speed,
+				from: start,
+			};
+			(source, StartAt::Beginning)
+		}
+		Command::Report { log, output } => return report::run(&log, output.as_deref()),
+		Command::Export {
+			log,
+			json,
+			csv,
+			sqlite: _,
+			output,
+		} => return export::run(&log, export::Format::new(json, csv), output.as_deref()),
+		Command::Split { log, output } => return split::run(&log, &output),
+		Command::Stats { log } => return stats::run(&log),
+		Command::Anonymize { input, output } => return anonymize::run(&input, &output),
+		Command::Serve { port } => {
+			let mut encs = encounters(&config, &settings)?;
+			encs.set_webhook(webhook(&config)?);
+			return serve::run(port, dir?, encs).await;
+		}
+		Command::Hub { bind, port } => return share::hub(&bind, port).await,
+		Command::Share { addr } => return share::run(&addr, dir?).await,
+	};
+	if let Source::File(path) | Source::Replay { path, .. } = &source {
+		if !Path::new(path).is_file() {
+			return Err(format!("{path}: no such file").into());
+		}
+	}
+
+	let rules = match config.as_deref().and_then(Rules::find) {
+		Some(path) => Rules::load(&path)?,
+		None => Rules::default(),
+	};
+	let encs = encounters(&config, &settings)?;
+	let hook = webhook(&config)?;
+
+	// personal bests from previously stored encounters, tonight's pulls are added as they end
+	let mut history = Bests::new();
+	if let Some(path) = Storage::default_path().filter(|p| Path::new(p).is_file()) {
+		Storage::open(path)?.load_bests(&mut history)?;
+	}
+
+	// the log picker lists the opened log's directory if there is no logs directory
+	let dir = match (dir, &source) {
+		(Ok(dir), _) => dir,
+		(Err(_), Source::File(path) | Source::Replay { path, .. }) => Path::new(path)
+			.parent()
+			.map_or_else(|| ".".to_owned(), |p| p.display().to_string()),
+		(Err(err), Source::Newest) => return Err(err.into()),
+	};
+
 	// setup terminal
 	enable_raw_mode()?;
 	let mut stdout = io::stdout();
@@ -94,41 +421,19 @@ async fn main() -> Result<(), Box<dyn Error>> {
 	let mut terminal = Terminal::new(backend)?;
 
 	// create app and run it
-	let app = App::new();
-	let npcs = app.npcs.clone();
-	let curr = app.curr.clone();
-	tokio::spawn(async move {
-		let dir = logs_path().unwrap();
-		//dbg!(logs_path());i
-		let last = Arc::new(Mutex::new(NaiveTime::default()));
-		parse(dir.as_str(), |enc, _| {
-			{
-				let mut last = last.lock().unwrap();
-				if enc.start.cmp(&last) != Ordering::Equal {
-					*last = enc.start;
-				}
-			}
-			{
-				let mut curr = curr.lock().unwrap();
-				*curr = enc.clone();
-			}
-
-			{
-				let mut npcs = npcs.lock().unwrap();
-				*npcs = enc
-					.npc_by_health(true)
-					.into_iter()
-					.map(|(id, v)| format!("{} ({})", id, fmt_num(v as f64)))
-					.collect::<Vec<String>>()
-					.join(", ");
-				if enc.is_boss() {
-					*npcs = format!("** {}", *npcs);
-				}
-			}
-		})
-		.await
-		.unwrap();
-	});
+	let mut app = App::new();
+	*app.alerts.lock().unwrap() = AlertEngine::new(rules);
+	*app.bests.lock().unwrap() = history;
+	app.dir = dir;
+	app.encs = encs;
+	app.webhook = hook;
+	app.config_modified = config_path
+		.as_deref()
+		.and_then(|p| p.metadata().and_then(|m| m.modified()).ok());
+	app.config_path = config_path;
+	app.config = settings;
+	app.fixed_dir = fixed_dir;
+	app.open(source, start.into());
 	let res = run_app(&mut terminal, app);
 
 	// restore terminal
@@ -147,9 +452,31 @@ async fn main() -> Result<(), Box<dyn Error>> {
 	Ok(())
 }
 
+// a copy is used for every log opened, with the phases from the config dir if there are any
+fn encounters(config: &Option<PathBuf>, settings: &Config) -> io::Result<Encounters> {
+	let mut encs = Encounters::new();
+	encs.set_options(settings.encounters.clone());
+	if let Some(path) = config.as_deref().and_then(PhaseDefs::find) {
+		encs.set_phases(PhaseDefs::load(&path)?.defs);
+	}
+	Ok(encs)
+}
+
+// webhook.toml in the config dir, only used when following the newest log
+fn webhook(config: &Option<PathBuf>) -> io::Result<Option<Webhook>> {
+	config
+		.as_deref()
+		.and_then(Webhook::find)
+		.map(|path| Webhook::load(&path))
+		.transpose()
+}
+
 fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<()> {
 	loop {
+		app.reload_config();
+		app.scanned();
 		terminal.draw(|f| ui(f, &mut app))?;
+		let tables = app.states.len();
 		let mut prev = 0;
 		let mut set_sel = |idx| {
 			app.selected = idx;
@@ -157,20 +484,37 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<(
 			prev = idx;
 		};
 
-		if event::poll(Duration::from_millis(250))? {
+		let keys = app.config.keys.clone();
+		if event::poll(Duration::from_millis(app.config.refresh_ms))? {
 			if let Event::Key(key) = event::read()? {
-				if key.kind == KeyEventKind::Press {
-					match key.code {
-						KeyCode::Char('q') => return Ok(()),
-						KeyCode::Char('1') => set_sel(0),
-						KeyCode::Char('2') => set_sel(1),
-						KeyCode::Char('3') => set_sel(2),
-						KeyCode::Char('4') => set_sel(3),
-						KeyCode::Esc => app.states[app.selected].select(None),
-						KeyCode::Down | KeyCode::Char('j') => app.next(),
-						KeyCode::Up | KeyCode::Char('k') => app.previous(),
+				let k = key.code;
+				if key.kind == KeyEventKind::Press && app.logs.is_some() {
+					match k {
+						_ if keys.quit == k => return Ok(()),
+						KeyCode::Enter => app.pick_log(),
+						KeyCode::Esc => app.logs = None,
+						KeyCode::Down => app.move_log(true),
+						KeyCode::Up => app.move_log(false),
+						_ if keys.logs == k => app.logs = None,
+						_ if keys.down == k => app.move_log(true),
+						_ if keys.up == k => app.move_log(false),
 						_ => {}
 					}
+				} else if key.kind == KeyEventKind::Press {
+					match k {
+						_ if keys.quit == k => return Ok(()),
+						_ if keys.logs == k => app.show_logs(),
+						_ if keys.deselect == k => app.states[app.selected].select(None),
+						_ if keys.down == k => app.next(),
+						_ if keys.up == k => app.previous(),
+						KeyCode::Down => app.next(),
+						KeyCode::Up => app.previous(),
+						_ => {
+							if let Some(i) = keys.tables.iter().position(|t| *t == k) {
+								set_sel(i.min(tables - 1));
+							}
+						}
+					}
 				}
 			}
 		}
@@ -178,12 +522,58 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<(
 }
 
 fn ui(f: &mut Frame, app: &mut App) {
+	if let Some((logs, st)) = &mut app.logs {
+		let mut rows = vec![(vec!["follow the newest log".to_owned()], 0.)];
+		rows.extend(logs.iter().map(|l| {
+			let date = l.date.map_or_else(
+				|| l.name.clone(),
+				|d| d.format("%Y-%m-%d %H:%M").to_string(),
+			);
+			let cells = vec![
+				date,
+				format!("{}B", fmt_num(l.size as f64)),
+				l.characters.join(", "),
+				l.areas.join(", "),
+				l.encounters.to_string(),
+			];
+			(cells, 0.)
+		}));
+		let title = match app.scan {
+			Some(_) => " Logs (scanning...) ",
+			None => " Logs (enter to open, esc to close) ",
+		};
+		let t = make_table(
+			title.to_owned(),
+			LOG_HEADER.as_slice(),
+			&widths(&app.config.columns.logs),
+			&rows,
+			true,
+			None,
+			&app.config.colors,
+		);
+		let Some(err) = &app.logs_error else {
+			f.render_stateful_widget(t, f.size(), st);
+			return;
+		};
+		let area = Layout::default()
+			.constraints([Constraint::Min(3), Constraint::Length(3)])
+			.split(f.size());
+		f.render_stateful_widget(t, area[0], st);
+		let err = Paragraph::new(err.clone())
+			.style(Style::default().fg(app.config.colors.error))
+			.block(Block::default().borders(Borders::ALL))
+			.wrap(Wrap { trim: true });
+		f.render_widget(err, area[1]);
+		return;
+	}
+
 	let (header, areas, footer) = calculate_layout(f.size());
 
+	let colors = &app.config.colors;
 	let create_block = |title| {
 		Block::default()
 			.borders(Borders::ALL)
-			.style(Style::default().fg(Color::Gray))
+			.style(Style::default().fg(colors.text))
 			.title(Span::styled(
 				title,
 				Style::default().add_modifier(Modifier::BOLD),
@@ -193,34 +583,97 @@ fn ui(f: &mut Frame, app: &mut App) {
 	let enc = app.curr.lock().unwrap();
 
 	let elapsed = enc.elapsed();
+	let sum = enc.summary();
+	let status = match enc.current_phase() {
+		Some(p) => format!("{} [{}]", enc.status(), p.name),
+		None => enc.status(),
+	};
 
-	let text = vec![Line::from(app.npcs.lock().unwrap().clone())];
-	let paragraph = Paragraph::new(text.clone())
-		.style(Style::default().fg(Color::Gray))
+	let mut text = vec![Line::from(app.npcs.lock().unwrap().clone())];
+	if let Some(err) = &app.config_error {
+		text.push(Line::styled(err.clone(), Style::default().fg(colors.error)));
+	}
+	if let Some(err) = app.parse_error.lock().unwrap().clone() {
+		text.push(Line::styled(err, Style::default().fg(colors.error)));
+	}
+	let paragraph = Paragraph::new(text)
+		.style(Style::default().fg(colors.text))
 		.block(create_block(format!(
-			" {} (elapsed: {:02}:{:02}) ",
+			" {} - {} (elapsed: {:02}:{:02}) | raid dps: {} | raid hps: {} | dtps: {} ",
 			enc.area,
+			status,
 			elapsed.num_minutes(),
-			elapsed.num_seconds() - (elapsed.num_minutes() * 60)
+			elapsed.num_seconds() - (elapsed.num_minutes() * 60),
+			fmt_num(sum.dps()),
+			fmt_num(sum.hps()),
+			fmt_num(sum.dtps()),
 		)))
 		.wrap(Wrap { trim: true });
-	f.render_widget(paragraph, header);
+
+	let alerts = app.alerts.lock().unwrap();
+	if alerts.rules.is_empty() {
+		f.render_widget(paragraph, header);
+	} else {
+		let header = Layout::default()
+			.direction(Direction::Horizontal)
+			.constraints([Constraint::Percentage(70), Constraint::Percentage(30)])
+			.split(header);
+		f.render_widget(paragraph, header[0]);
+
+		let timers = alerts
+			.active()
+			.map(|(a, left)| format!("{}: {}s", a.text, left.num_seconds()))
+			.collect::<Vec<_>>()
+			.join(", ");
+		let timers = Paragraph::new(vec![Line::from(timers)])
+			.style(Style::default().fg(colors.timers))
+			.block(create_block(" Timers ".to_owned()))
+			.wrap(Wrap { trim: true });
+		f.render_widget(timers, header[1]);
+	}
 
 	let (t, states) = {
-		let vec = match app.selected {
-			0 => enc.heals_out(),
-			1 => enc.dmg_out(),
+		let rows = match app.selected {
+			0 => enc.heals_out_rows(),
+			1 => enc.dmg_out_rows(),
+			2 => enc.dmg_out_rows_by_role(Role::Dps),
 			_ => Vec::new(),
 		};
+		let bests = app.bests.lock().unwrap();
+		let vec = rows
+			.iter()
+			.map(|row| {
+				let mut r = row.cells();
+				let cmp = enc.player(&row.id).and_then(|p| bests.compare(&enc, p));
+				let (pb, prev) = match (app.selected, cmp) {
+					(_, None) => (None, None),
+					(0, Some(c)) => (c.hps_vs_best(), c.hps_vs_previous()),
+					(_, Some(c)) => (c.dps_vs_best(), c.dps_vs_previous()),
+				};
+				r.push(fmt_delta(pb));
+				r.push(fmt_delta(prev));
+				(r, row.meter.xps)
+			})
+			.collect::<Vec<_>>();
+		// players are found by id, names are shown with their discipline
+		let me = rows
+			.iter()
+			.position(|r| enc.local_player.as_ref() == Some(&r.id));
+		if let Some(idx) = me {
+			if !app.auto_selected[app.selected] {
+				app.auto_selected[app.selected] = true;
+				app.states[app.selected].select(Some(idx));
+			}
+		}
 		let states = if let Some(idx) = app.states[app.selected].selected() {
 			if idx >= vec.len() {
 				app.states[app.selected].select(None);
 				None
-			} else if let Some(p) = enc.player_by_name(&vec[idx].0[0]) {
+			} else if let Some(p) = enc.player(&rows[idx].id) {
 				let elps = enc.elapsed().num_seconds();
 				let x_out = match app.selected {
 					0 => p.heal_out_to_vec(elps),
-					1 => p.dmg_out_to_vec(elps),
+					1 | 2 => p.dmg_out_to_vec(elps),
 					_ => vec![],
 				};
 				let mut spells_out = p.spells_out_to_vec(elps);
@@ -251,21 +704,39 @@ fn ui(f: &mut Frame, app: &mut App) {
 					vec!["# Interrupted".to_owned(), fmt_num(p.interrupted as f64)],
 					0.,
 				));
+				if !p.resources.is_empty() {
+					spells_out.push((vec![], 0.));
+					spells_out.push((
+						vec![
+							"Resource".to_owned(),
+							"spent".to_owned(),
+							"restored".to_owned(),
+							"min".to_owned(),
+							"max".to_owned(),
+						],
+						0.,
+					));
+					spells_out.append(&mut p.resources_to_vec());
+				}
 
 				Some((
 					make_table(
 						format!(" Spells for {} ", p.id.name),
 						XPS_HEADER.as_slice(),
-						XPS_COLUMN_WIDTHS.as_slice(),
+						&widths(&app.config.columns.xps),
 						&spells_out,
 						false,
+						None,
+						&app.config.colors,
 					),
 					make_table(
 						format!(" Targets for {} ", p.id.name),
 						XPS_HEADER.as_slice(),
-						XPS_COLUMN_WIDTHS.as_slice(),
+						&widths(&app.config.columns.xps),
 						&x_out,
 						false,
+						None,
+						&app.config.colors,
 					),
 				))
 			} else {
@@ -274,18 +745,27 @@ fn ui(f: &mut Frame, app: &mut App) {
 		} else {
 			None
 		};
-		let title = if app.selected == 0 {
-			" * Healing | Damage (2) "
-		} else {
-			" Healing (1) | * Damage "
-		};
+		let title = TABLE_NAMES
+			.iter()
+			.enumerate()
+			.map(|(i, n)| {
+				if i == app.selected {
+					format!("* {}", n)
+				} else {
+					format!("{} ({})", n, i + 1)
+				}
+			})
+			.collect::<Vec<_>>()
+			.join(" | ");
 		(
 			make_table(
-				title.to_owned(),
-				XPS_HEADER.as_slice(),
-				XPS_COLUMN_WIDTHS.as_slice(),
+				format!(" {} ", title),
+				PLAYER_HEADER.as_slice(),
+				&widths(&app.config.columns.player),
 				&vec,
 				true,
+				me,
+				&app.config.colors,
 			),
 			states,
 		)
@@ -298,6 +778,13 @@ fn ui(f: &mut Frame, app: &mut App) {
 	}
 }
 
+fn fmt_delta(pct: Option<f64>) -> String {
+	match pct {
+		Some(pct) => format!("{:+.1}%", pct),
+		None => "-".to_owned(),
+	}
+}
+
 fn calculate_layout(area: Rect) -> (Rect, Vec<Vec<Rect>>, Rect) {
 	let layout = Layout::default()
 		.direction(Direction::Vertical)
@@ -329,15 +816,18 @@ fn make_table<'a>(
 	widths: &[Constraint],
 	vec: &[(Vec<String>, f64)],
 	selected: bool,
+	me: Option<usize>,
+	colors: &Colors,
 ) -> Table<'a> {
 	let selected_style = Style::default().add_modifier(Modifier::REVERSED);
-	let normal_style = Style::default().bg(Color::Blue);
+	let normal_style = Style::default().bg(colors.header);
+	let me_style = Style::default().fg(colors.me).add_modifier(Modifier::BOLD);
 	let header_cells = header
 		.iter()
-		.map(|h| Cell::from(*h).style(Style::default().fg(Color::Black)));
+		.map(|h| Cell::from(*h).style(Style::default().fg(colors.header_text)));
 	let header = Row::new(header_cells).style(normal_style).height(1);
 
-	let rows = vec.iter().map(|item| {
+	let rows = vec.iter().enumerate().map(|(i, item)| {
 		let height =
 			item.0
 				.iter()
@@ -345,7 +835,12 @@ fn make_table<'a>(
 				.max()
 				.unwrap_or(0) + 1;
 		let cells = item.0.iter().map(|c| Cell::from(c.clone()));
-		Row::new(cells).height(height as u16)
+		let row = Row::new(cells).height(height as u16);
+		if me == Some(i) {
+			row.style(me_style)
+		} else {
+			row
+		}
 	});
 	let t = Table::new(rows, widths)
 		.header(header)
@@ -353,7 +848,7 @@ fn make_table<'a>(
 			Block::default()
 				.borders(Borders::ALL)
 				.border_style(if selected {
-					Style::default().fg(Color::Green)
+					Style::default().fg(colors.active)
 				} else {
 					Style::default()
 				})
@@ -0,0 +1,24 @@
+use std::{
+	error::Error,
+	fs::File,
+	io::{self, BufWriter, Write},
+	path::Path,
+};
+
+use swtorlib::{parser::parse_file, report};
+
+pub fn run(log: &str, out: Option<&str>) -> Result<(), Box<dyn Error>> {
+	let encs = parse_file(log)?;
+	let title = Path::new(log)
+		.file_stem()
+		.map_or_else(|| log.to_owned(), |s| s.to_string_lossy().to_string());
+
+	let w: Box<dyn Write> = match out {
+		Some(p) => Box::new(File::create(p)?),
+		None => Box::new(io::stdout()),
+	};
+	let mut w = BufWriter::new(w);
+	report::write_html(&mut w, &title, &encs)?;
+	w.flush()?;
+	Ok(())
+}
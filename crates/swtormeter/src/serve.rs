@@ -0,0 +1,224 @@
+use std::{
+	error::Error,
+	sync::{Arc, Mutex},
+};
+
+use axum::{
+	extract::{
+		ws::{Message, WebSocket, WebSocketUpgrade},
+		State,
+	},
+	http::{header, StatusCode},
+	response::{IntoResponse, Response},
+	routing::get,
+	Json, Router,
+};
+use serde_json::{json, Value};
+use swtorlib::{
+	metrics::Metrics,
+	parse_with,
+	parser::{
+		encounter::{Encounter, Encounters},
+		reader::Start,
+	},
+};
+use tokio::{net::TcpListener, sync::broadcast};
+
+// shared between the parse callback and the http handlers
+pub struct Overlay {
+	curr: Mutex<Option<Encounter>>,
+	// summaries of finished encounters, oldest first
+	done: Mutex<Vec<Value>>,
+	tx: broadcast::Sender<String>,
+}
+
+impl Overlay {
+	pub fn new() -> Self {
+		Self {
+			curr: Mutex::new(None),
+			done: Mutex::new(Vec::new()),
+			tx: broadcast::channel(64).0,
+		}
+	}
+
+	// called for every parsed line
+	pub fn update(&self, enc: &Encounter) {
+		let mut curr = self.curr.lock().unwrap();
+		if let Some(prev) = curr.as_ref().filter(|c| c.start != enc.start) {
+			self.done.lock().unwrap().push(summary(prev));
+		}
+		*curr = Some(enc.clone());
+
+		if self.tx.receiver_count() > 0 {
+			if let Ok(js) = serde_json::to_string(enc) {
+				let _ = self.tx.send(js);
+			}
+		}
+	}
+}
+
+impl Default for Overlay {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+// encs can come with a webhook or options set
+pub async fn run(port: u16, dir: String, encs: Encounters) -> Result<(), Box<dyn Error>> {
+	let overlay = Arc::new(Overlay::new());
+	let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+	eprintln!("listening on http://{}", listener.local_addr()?);
+
+	let ov = overlay.clone();
+	tokio::spawn(async move {
+		if let Err(err) = parse_with(&dir, encs, Start::Beginning, |enc, _| ov.update(enc)).await {
+			eprintln!("{dir}: {err}");
+		}
+	});
+
+	axum::serve(listener, router(overlay)).await?;
+	Ok(())
+}
+
+pub fn router(overlay: Arc<Overlay>) -> Router {
+	Router::new()
+		.route("/encounter/current", get(current))
+		.route("/encounters", get(encounters))
+		.route("/ws", get(ws))
+		.route("/metrics", get(metrics))
+		.with_state(overlay)
+}
+
+// overlays are usually loaded from a file:// url
+fn cors(v: impl IntoResponse) -> Response {
+	([(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")], v).into_response()
+}
+
+async fn current(State(ov): State<Arc<Overlay>>) -> Response {
+	match ov.curr.lock().unwrap().as_ref() {
+		Some(enc) => cors(Json(enc)),
+		None => cors(StatusCode::NOT_FOUND),
+	}
+}
+
+async fn encounters(State(ov): State<Arc<Overlay>>) -> Response {
+	let mut all = ov.done.lock().unwrap().clone();
+	if let Some(enc) = ov.curr.lock().unwrap().as_ref() {
+		all.push(summary(enc));
+	}
+	cors(Json(all))
+}
+
+async fn metrics() -> Response {
+	(
+		[(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+		Metrics::global().render(),
+	)
+		.into_response()
+}
+
+async fn ws(State(ov): State<Arc<Overlay>>, up: WebSocketUpgrade) -> Response {
+	up.on_upgrade(move |socket| push_updates(ov, socket))
+}
+
+async fn push_updates(ov: Arc<Overlay>, mut socket: WebSocket) {
+	let mut rx = ov.tx.subscribe();
+	let first = ov
+		.curr
+		.lock()
+		.unwrap()
+		.as_ref()
+		.and_then(|enc| serde_json::to_string(enc).ok());
+	if let Some(js) = first {
+		if socket.send(Message::Text(js)).await.is_err() {
+			return;
+		}
+	}
+
+	loop {
+		match rx.recv().await {
+			Ok(js) => {
+				if socket.send(Message::Text(js)).await.is_err() {
+					return;
+				}
+			}
+			// a slow client only needs the latest state
+			Err(broadcast::error::RecvError::Lagged(_)) => continue,
+			Err(broadcast::error::RecvError::Closed) => return,
+		}
+	}
+}
+
+fn summary(enc: &Encounter) -> Value {
+	let sum = enc.summary();
+	json!({
+		"start": enc.start.to_string(),
+		"area": enc.area,
+		"name": enc.name(),
+		"status": enc.status(),
+		"outcome": enc.outcome().name(),
+		"elapsed": sum.elapsed,
+		"players": sum.raid.players,
+		"deaths": sum.raid.deaths,
+		"dps": sum.dps(),
+		"hps": sum.hps(),
+		"dtps": sum.dtps(),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	use futures_util::StreamExt;
+	use swtorlib::parser::line::Line;
+	use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+	static BRONTES: &str = include_str!("../../swtorlib/tests/data/brontes.txt");
+
+	async fn get(addr: std::net::SocketAddr, path: &str) -> String {
+		let mut s = tokio::net::TcpStream::connect(addr).await.unwrap();
+		let req = format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n");
+		s.write_all(req.as_bytes()).await.unwrap();
+		let mut out = String::new();
+		s.read_to_string(&mut out).await.unwrap();
+		out
+	}
+
+	#[tokio::test]
+	async fn serve_local() {
+		let ov = Arc::new(Overlay::new());
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let addr = listener.local_addr().unwrap();
+		let app = router(ov.clone());
+		tokio::spawn(async move { axum::serve(listener, app).await });
+
+		assert!(get(addr, "/encounter/current")
+			.await
+			.starts_with("HTTP/1.1 404"));
+
+		let mut encs = Encounters::new();
+		// stops before pull 2 is over
+		for l in BRONTES.lines().take(8) {
+			encs.push(Line::new(l).unwrap(), &|enc, _| ov.update(enc));
+		}
+
+		let body = get(addr, "/encounters").await;
+		assert!(body.contains("\"status\":\"Brontes (8m Veteran) pull 1, wipe at 77%\""));
+		assert!(body.contains("\"status\":\"Brontes (8m Veteran) pull 2, in progress at 0%\""));
+		assert!(get(addr, "/encounter/current")
+			.await
+			.contains("Dread Master Brontes"));
+
+		// the current encounter is sent as soon as a client connects
+		let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{addr}/ws"))
+			.await
+			.unwrap();
+		let msg = ws.next().await.unwrap().unwrap();
+		assert!(msg.into_text().unwrap().contains("The Dread Fortress"));
+
+		let body = get(addr, "/metrics").await;
+		assert!(body.contains("text/plain; version=0.0.4"));
+		assert!(body.contains("# TYPE swtormeter_lines_parsed_total counter"));
+	}
+}
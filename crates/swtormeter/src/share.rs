@@ -0,0 +1,139 @@
+use std::{
+	error::Error,
+	sync::{Arc, Mutex},
+};
+
+use chrono::{Duration, NaiveTime};
+use swtorlib::{
+	parser::{
+		action::Action,
+		encounter::{Encounter, Encounters},
+		line::Line,
+		namedid::NamedID,
+		reader::{Reader, Start},
+		utils::fmt_num,
+	},
+	share::{Client, Group, Hello, Hub, Message, Summary},
+};
+use tokio::sync::mpsc;
+
+// runs the relay every raid member's meter connects to
+pub async fn hub(bind: &str, port: u16) -> Result<(), Box<dyn Error>> {
+	let hub = Hub::bind((bind, port)).await?;
+	eprintln!("hub listening on {}", hub.local_addr()?);
+	hub.run().await?;
+	Ok(())
+}
+
+// sends the local view of the current encounter once a second and prints the group's view
+pub async fn run(addr: &str, dir: String) -> Result<(), Box<dyn Error>> {
+	let hello = |name: String| Hello {
+		name,
+		version: env!("CARGO_PKG_VERSION").to_owned(),
+	};
+	let mut client = Client::connect(addr, hello(String::new())).await?;
+
+	let (tx, mut rx) = mpsc::unbounded_channel();
+	// the encounter start, the time of the last update sent and the lines since then
+	let last = Arc::new(Mutex::new((NaiveTime::MIN, NaiveTime::MIN, Vec::new())));
+	// the log is read from the start for the area and the local player, but only what is logged
+	// after joining is sent, the old fights in it would flood the hub
+	let (mut lines, caught_up) = Reader::follow_dir(&dir, Start::Beginning).await?;
+	tokio::spawn(async move {
+		let mut encs = Encounters::new();
+		let mut n = 0;
+		while let Some(l) = lines.recv().await {
+			n += 1;
+			let live = caught_up.is_live(n);
+			encs.push(l, &|enc, l| {
+				if !live {
+					return;
+				}
+				let mut last = last.lock().unwrap();
+				if last.0 != enc.start {
+					// the other peers need the area and the start of combat to merge the lines
+					last.2 = encounter_start(enc);
+				}
+				last.2.push(l.clone());
+				let ended = matches!(l.action, Action::ExitCombat);
+				if last.0 == enc.start && l.ts - last.1 < Duration::seconds(1) && !ended {
+					return;
+				}
+				last.0 = enc.start;
+				last.1 = l.ts;
+				let lines = std::mem::take(&mut last.2);
+				let name = enc.local_player.as_ref().map(|p| p.name.clone());
+				let _ = tx.send((name, Summary::new(enc), lines));
+			});
+		}
+	});
+
+	let mut group = Group::new();
+	let mut me = String::new();
+	loop {
+		tokio::select! {
+			Some((name, sum, lines)) = rx.recv() => {
+				if let Some(name) = name.filter(|n| *n != me) {
+					me = name;
+					client.send(&Message::Hello(hello(me.clone()))).await?;
+					group.update(Group::LOCAL, Message::Hello(hello(me.clone())));
+				}
+				client.send(&Message::Summary(sum.clone())).await?;
+				group.update(Group::LOCAL, Message::Summary(sum));
+				let lines = Message::Lines(lines);
+				client.send(&lines).await?;
+				group.update(Group::LOCAL, lines);
+			}
+			res = client.recv() => {
+				let (peer, m) = res?;
+				let is_summary = matches!(m, Message::Summary(_));
+				group.update(peer, m);
+				if is_summary {
+					print_group(&group);
+				}
+			}
+		}
+	}
+}
+
+// the lines process is not called for
+fn encounter_start(enc: &Encounter) -> Vec<Line> {
+	let area = Action::AreaEntered {
+		area: NamedID {
+			id: enc.area_id,
+			name: enc.area.clone(),
+		},
+		difficulty: enc.difficulty,
+	};
+	[area, Action::EnterCombat]
+		.into_iter()
+		.map(|action| Line {
+			ts: enc.start,
+			action,
+			..Line::default()
+		})
+		.collect()
+}
+
+fn print_group(group: &Group) {
+	let peers = group
+		.peers()
+		.map(|(_, n)| n.as_str())
+		.filter(|n| !n.is_empty())
+		.collect::<Vec<_>>()
+		.join(", ");
+	println!("-- group: {peers}");
+	if let Some(e) = group.merged() {
+		println!("{}, {} players", e.status(), e.players.len());
+	}
+	for p in group.combined() {
+		println!(
+			"{:<24} {:>10} dmg {:>10} heal {:>10} taken {} deaths",
+			p.name,
+			fmt_num(p.dmg_out as f64),
+			fmt_num(p.heal_out as f64),
+			fmt_num(p.dmg_in as f64),
+			p.deaths
+		);
+	}
+}
@@ -0,0 +1,10 @@
+use std::error::Error;
+
+use swtorlib::parser::split::split_file;
+
+pub fn run(log: &str, out: &str) -> Result<(), Box<dyn Error>> {
+	for path in split_file(log, out)? {
+		println!("{path}");
+	}
+	Ok(())
+}
@@ -0,0 +1,27 @@
+use std::error::Error;
+
+use swtorlib::parser::{parse_file, utils::fmt_num};
+
+// one line per encounter
+pub fn run(log: &str) -> Result<(), Box<dyn Error>> {
+	let encs = parse_file(log)?;
+	println!(
+		"{:<12} {:<48} {:>6} {:>8} {:>10} {:>10} {:>10} {:>6}",
+		"start", "encounter", "time", "players", "dps", "hps", "dtps", "deaths"
+	);
+	for e in &encs {
+		let sum = e.summary();
+		println!(
+			"{:<12} {:<48} {:>6} {:>8} {:>10} {:>10} {:>10} {:>6}",
+			e.start.format("%H:%M:%S").to_string(),
+			e.status(),
+			format!("{}:{:02}", sum.elapsed / 60, sum.elapsed % 60),
+			sum.raid.players,
+			fmt_num(sum.dps()),
+			fmt_num(sum.hps()),
+			fmt_num(sum.dtps()),
+			sum.raid.deaths,
+		);
+	}
+	Ok(())
+}